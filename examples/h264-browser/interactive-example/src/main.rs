@@ -1,4 +1,3 @@
-use std::str::FromStr;
 use gst::{Element};
 use gst::prelude::*;
 use anyhow::Result;
@@ -7,7 +6,6 @@ use tokio::io;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use webrtcredux::{RTCIceConnectionState, RTCSdpType};
 use tokio::runtime::Handle;
-use webrtcredux::sdp::LineEnding::LF;
 
 use webrtcredux::webrtcredux::{
     sdp::{SDP},
@@ -116,7 +114,7 @@ async fn main() -> Result<()> {
 
     let line = clipboard_handle.get_contents().expect("Failed to get clipboard contents");
     let sdp_offer_from_b64 = decode(line.as_str())?;
-    let offer = SDP::from_str(&sdp_offer_from_b64).expect("Failed to parse SDP");
+    let (offer, _) = SDP::from_jsep(&sdp_offer_from_b64).expect("Failed to parse SDP");
 
     pipeline.set_state(gst::State::Playing)?;
 
@@ -137,22 +135,12 @@ async fn main() -> Result<()> {
 
     webrtcredux.set_remote_description(&offer, RTCSdpType::Offer).await?;
 
-    let answer = webrtcredux.create_answer(None).await?;
+    let answer = webrtcredux.create_complete_answer().await?;
 
-    let mut gather_complete = webrtcredux.gathering_complete_promise().await?;
-
-    webrtcredux.set_local_description(&answer, RTCSdpType::Answer).await?;
-    
-    let _ = gather_complete.recv().await;
-
-    if let Ok(Some(local_desc)) = webrtcredux.local_description().await {
-        let b64 = base64::encode(local_desc.to_string(LF));
-        clipboard_handle.set_contents(b64.clone()).expect("Failed to set clipboard contents");
-        println!("Base64 Session Description for the browser copied to the clipboard", );
-        println!("{}", b64);
-    } else {
-        println!("generate local_description failed!");
-    }
+    let b64 = base64::encode(answer.to_jsep(RTCSdpType::Answer));
+    clipboard_handle.set_contents(b64.clone()).expect("Failed to set clipboard contents");
+    println!("Base64 Session Description for the browser copied to the clipboard", );
+    println!("{}", b64);
     
     tokio::signal::ctrl_c().await?;
 
@@ -21,7 +21,7 @@ pc.ontrack = function (event) {
 pc.oniceconnectionstatechange = e => log(pc.iceConnectionState)
 pc.onicecandidate = event => {
     if (event.candidate === null) {
-        document.getElementById('localSessionDescription').value = btoa(pc.localDescription.sdp)
+        document.getElementById('localSessionDescription').value = btoa(JSON.stringify(pc.localDescription))
     }
 }
 
@@ -37,7 +37,7 @@ window.startSession = () => {
     }
 
     try {
-        pc.setRemoteDescription(new RTCSessionDescription({type: 'answer', sdp: atob(sd)}))
+        pc.setRemoteDescription(new RTCSessionDescription(JSON.parse(atob(sd))))
     } catch (e) {
         alert(e)
     }
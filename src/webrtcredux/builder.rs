@@ -0,0 +1,95 @@
+use tokio::runtime::Handle;
+
+use super::{RTCBundlePolicy, RTCConfiguration, RTCIceServer, RTCRtpCodecParameters, RTCSdpSemantics, RTPCodecType, WebRtcRedux, WebRtcReduxError};
+
+/// Collects the configuration that has to be known before the peer connection starts and creates the element with it.
+///
+/// Unlike the setters on [`WebRtcRedux`], which only log an error once the connection is running, every option is
+/// checked when building.
+#[derive(Default)]
+pub struct WebRtcReduxBuilder {
+    name: Option<String>,
+    config: Option<RTCConfiguration>,
+    ice_servers: Vec<RTCIceServer>,
+    bundle_policy: Option<RTCBundlePolicy>,
+    sdp_semantics: Option<RTCSdpSemantics>,
+    codec_preferences: Vec<(RTPCodecType, Vec<RTCRtpCodecParameters>)>,
+    handle: Option<Handle>,
+}
+
+impl WebRtcReduxBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Base configuration, ICE servers and the bundle policy set on the builder apply on top of it
+    pub fn configuration(mut self, config: RTCConfiguration) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Appends to the ICE servers of the configuration
+    pub fn ice_servers(mut self, mut ice_servers: Vec<RTCIceServer>) -> Self {
+        self.ice_servers.append(&mut ice_servers);
+        self
+    }
+
+    pub fn bundle_policy(mut self, bundle_policy: RTCBundlePolicy) -> Self {
+        self.bundle_policy = Some(bundle_policy);
+        self
+    }
+
+    /// webrtc-rs only implements unified plan, building fails for any other semantics
+    pub fn sdp_semantics(mut self, sdp_semantics: RTCSdpSemantics) -> Self {
+        self.sdp_semantics = Some(sdp_semantics);
+        self
+    }
+
+    /// Codecs every pad of `kind` prefers, unless overridden per pad with [`WebRtcRedux::set_codec_preferences`]
+    pub fn codec_preferences(mut self, kind: RTPCodecType, codecs: Vec<RTCRtpCodecParameters>) -> Self {
+        self.codec_preferences.retain(|(existing, _)| *existing != kind);
+        self.codec_preferences.push((kind, codecs));
+        self
+    }
+
+    /// Runtime the peer connection is driven on instead of the element's own
+    pub fn tokio_runtime(mut self, handle: Handle) -> Self {
+        self.handle = Some(handle);
+        self
+    }
+
+    pub fn build(self) -> Result<WebRtcRedux, WebRtcReduxError> {
+        if let Some(sdp_semantics) = self.sdp_semantics.filter(|semantics| !matches!(semantics, RTCSdpSemantics::UnifiedPlan | RTCSdpSemantics::Unspecified)) {
+            return Err(WebRtcReduxError::UnsupportedSdpSemantics(sdp_semantics));
+        }
+        if let Some((kind, _)) = self.codec_preferences.iter().find(|(kind, _)| *kind == RTPCodecType::Unspecified) {
+            return Err(WebRtcReduxError::UnsupportedMediaKind(*kind));
+        }
+
+        let element = match &self.name {
+            Some(name) => gst::glib::Object::new::<WebRtcRedux>(&[("name", name)]),
+            None => WebRtcRedux::default(),
+        };
+
+        if let Some(config) = self.config {
+            element.set_configuration(config);
+        }
+        element.add_ice_servers(self.ice_servers);
+        if let Some(bundle_policy) = self.bundle_policy {
+            element.set_bundle_policy(bundle_policy);
+        }
+        for (kind, codecs) in self.codec_preferences {
+            element.set_default_codec_preferences(kind, codecs);
+        }
+        if let Some(handle) = self.handle {
+            element.set_tokio_runtime(handle);
+        }
+
+        Ok(element)
+    }
+}
@@ -0,0 +1,60 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use gst::debug;
+use webrtc::data_channel::data_channel_state::RTCDataChannelState;
+use webrtc::data_channel::RTCDataChannel;
+
+use super::{WebRtcReduxError, CAT};
+
+/// How often the buffered amount is checked while draining
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Snapshot of a data channel in the registry returned by `WebRtcRedux::data_channels`
+#[derive(Clone)]
+pub struct DataChannelInfo {
+    pub label: String,
+    pub id: u16,
+    pub ready_state: RTCDataChannelState,
+    /// Bytes queued but not sent yet
+    pub buffered_amount: usize,
+    pub channel: Arc<RTCDataChannel>,
+}
+
+pub trait DataChannelExt {
+    /// Waits for all buffered data to be sent before closing the channel.
+    ///
+    /// The channel is closed even if the buffer didn't drain within `timeout`, in which case an error is returned.
+    fn close_graceful(&self, timeout: Duration) -> Pin<Box<dyn Future<Output = Result<(), WebRtcReduxError>> + Send + '_>>;
+}
+
+impl DataChannelExt for RTCDataChannel {
+    fn close_graceful(&self, timeout: Duration) -> Pin<Box<dyn Future<Output = Result<(), WebRtcReduxError>> + Send + '_>> {
+        Box::pin(async move {
+            let drained = tokio::time::timeout(timeout, async {
+                while self.buffered_amount().await > 0 {
+                    tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+                }
+            })
+            .await
+            .is_ok();
+
+            if !drained {
+                let buffered = self.buffered_amount().await;
+                debug!(CAT, "Data channel '{}' still has {} bytes buffered, closing anyway", self.label(), buffered);
+            }
+
+            if let Err(e) = self.close().await {
+                return Err(WebRtcReduxError::DataChannelFailed("close", e));
+            }
+
+            if drained {
+                Ok(())
+            } else {
+                Err(WebRtcReduxError::DataChannelNotDrained(self.label().to_string()))
+            }
+        })
+    }
+}
@@ -0,0 +1,121 @@
+use std::fmt::{Display, Formatter};
+
+use gst::ErrorMessage;
+use webrtc::ice_transport::ice_connection_state::RTCIceConnectionState;
+use webrtc::peer_connection::policy::sdp_semantics::RTCSdpSemantics;
+use webrtc::rtp_transceiver::rtp_codec::RTPCodecType;
+
+use super::sdp::ParseError;
+
+#[derive(Debug)]
+pub enum WebRtcReduxError {
+    /// The peer connection hasn't been started yet, or has already been stopped
+    NotStarted,
+    /// The peer connection is already running
+    AlreadyStarted,
+    /// The pad name doesn't follow the `video_<id>`/`audio_<id>` scheme
+    InvalidPadName(String),
+    /// No pad with this name has been requested
+    PadNotFound(String),
+    /// The operation only applies to video pads
+    NotVideoPad(String),
+    /// The track for this pad has already been created, so it can't be configured anymore
+    TrackAlreadyCreated(String),
+    /// The codec of an inserted encoder doesn't match the media kind of the pad
+    EncoderKindMismatch(String),
+    /// The MediaEngine has no codec registered for the track of this pad, so it can't be negotiated
+    CodecNotRegistered(String, &'static str),
+    /// The audio caps of this pad have a rate its codec can't be sent with
+    UnsupportedSampleRate(String, i32),
+    /// An answer was requested before a remote offer was set
+    NoRemoteOffer,
+    /// The local and remote descriptions don't settle the DTLS role yet
+    DtlsRoleNotNegotiated,
+    /// ICE ended up in this state instead of connecting
+    IceConnectionFailed(RTCIceConnectionState),
+    /// The local description is missing or has no ICE credentials
+    NoIceCredentials,
+    /// A description generated by or given to the peer connection couldn't be parsed
+    SdpParse(ParseError),
+    /// Creating, setting or applying a description or candidate, described by the first field, failed
+    NegotiationFailure(&'static str, webrtc::Error),
+    /// Any other peer connection operation, described by the first field, failed inside webrtc-rs
+    WebRtcInternal(&'static str, webrtc::Error),
+    /// A transceiver couldn't be created
+    TransceiverFailed(webrtc::Error),
+    /// A data channel operation, described by the first field, failed
+    DataChannelFailed(&'static str, webrtc::Error),
+    /// A data channel was closed before its buffered data was sent
+    DataChannelNotDrained(String),
+    /// Creating or closing the peer connection on the runtime failed
+    Runtime(String),
+    /// A request to the signaling server, described by the first field, failed
+    SignalingFailed(String, String),
+    /// webrtc-rs only implements unified plan
+    UnsupportedSdpSemantics(RTCSdpSemantics),
+    /// The option only applies to audio or video
+    UnsupportedMediaKind(RTPCodecType),
+}
+
+impl Display for WebRtcReduxError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WebRtcReduxError::NotStarted => write!(f, "Peer connection is not set, make sure plugin is started"),
+            WebRtcReduxError::AlreadyStarted => write!(f, "Peer connection has already been started"),
+            WebRtcReduxError::InvalidPadName(name) => write!(f, "Pad with name '{}' is invalid", name),
+            WebRtcReduxError::PadNotFound(name) => write!(f, "Pad with name '{}' not found", name),
+            WebRtcReduxError::NotVideoPad(name) => write!(f, "Pad '{}' is not a video pad", name),
+            WebRtcReduxError::TrackAlreadyCreated(name) => write!(f, "Track for pad '{}' has already been created", name),
+            WebRtcReduxError::EncoderKindMismatch(name) => write!(f, "Encoder codec doesn't match the media kind of pad '{}'", name),
+            WebRtcReduxError::CodecNotRegistered(name, mime) => write!(f, "Codec {} of pad '{}' is not registered in the MediaEngine", mime, name),
+            WebRtcReduxError::UnsupportedSampleRate(name, rate) => write!(f, "Sample rate {} of pad '{}' is not supported by its codec", rate, name),
+            WebRtcReduxError::NoRemoteOffer => write!(f, "No remote offer has been set, call set_remote_description with an offer before creating an answer"),
+            WebRtcReduxError::DtlsRoleNotNegotiated => write!(f, "DTLS role is not negotiated yet, set both descriptions first"),
+            WebRtcReduxError::IceConnectionFailed(state) => write!(f, "ICE connection {} before connecting", state),
+            WebRtcReduxError::NoIceCredentials => write!(f, "Local description has no ICE credentials, set a local description first"),
+            WebRtcReduxError::SdpParse(e) => write!(f, "Failed to parse SDP: {:?}", e),
+            WebRtcReduxError::NegotiationFailure(operation, e) => write!(f, "Failed to {}: {:?}", operation, e),
+            WebRtcReduxError::WebRtcInternal(operation, e) => write!(f, "Failed to {}: {:?}", operation, e),
+            WebRtcReduxError::TransceiverFailed(e) => write!(f, "Failed to create transceiver: {:?}", e),
+            WebRtcReduxError::DataChannelFailed(operation, e) => write!(f, "Failed to {} data channel: {:?}", operation, e),
+            WebRtcReduxError::DataChannelNotDrained(label) => write!(f, "Data channel '{}' closed before its buffer drained", label),
+            WebRtcReduxError::Runtime(e) => write!(f, "Runtime task failed: {}", e),
+            WebRtcReduxError::SignalingFailed(operation, e) => write!(f, "Failed to {}: {}", operation, e),
+            WebRtcReduxError::UnsupportedSdpSemantics(semantics) => write!(f, "SDP semantics {} are not supported, only unified-plan is", semantics),
+            WebRtcReduxError::UnsupportedMediaKind(kind) => write!(f, "Media kind {} is not supported, only audio and video are", kind),
+        }
+    }
+}
+
+impl std::error::Error for WebRtcReduxError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WebRtcReduxError::NegotiationFailure(_, e)
+            | WebRtcReduxError::WebRtcInternal(_, e)
+            | WebRtcReduxError::TransceiverFailed(e)
+            | WebRtcReduxError::DataChannelFailed(_, e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for WebRtcReduxError {
+    fn from(e: ParseError) -> Self {
+        WebRtcReduxError::SdpParse(e)
+    }
+}
+
+impl From<WebRtcReduxError> for ErrorMessage {
+    fn from(e: WebRtcReduxError) -> Self {
+        match e {
+            WebRtcReduxError::InvalidPadName(_) | WebRtcReduxError::PadNotFound(_) => {
+                gst::error_msg!(gst::ResourceError::NotFound, [&e.to_string()])
+            }
+            WebRtcReduxError::DataChannelNotDrained(_) => gst::error_msg!(gst::ResourceError::Busy, [&e.to_string()]),
+            WebRtcReduxError::NotStarted => gst::error_msg!(gst::CoreError::StateChange, [&e.to_string()]),
+            WebRtcReduxError::NegotiationFailure(..) => gst::error_msg!(gst::StreamError::Failed, [&e.to_string()]),
+            WebRtcReduxError::SdpParse(_) => gst::error_msg!(gst::StreamError::Decode, [&e.to_string()]),
+            _ => gst::error_msg!(gst::ResourceError::Failed, [&e.to_string()]),
+        }
+    }
+}
@@ -1,23 +1,44 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
 use std::sync::Arc;
 
 use gst::glib;
 use gst::prelude::*;
 use gst::subclass::prelude::ObjectSubclassExt;
-use gst::ErrorMessage;
 
 mod sender;
 
+mod pad;
+
+mod builder;
+
 mod imp;
 
+mod data_channel;
+
+mod error;
+
+mod retransmission;
+
+mod signaling;
+
 pub use imp::*;
+pub use builder::WebRtcReduxBuilder;
+pub use data_channel::{DataChannelExt, DataChannelInfo};
+pub use error::WebRtcReduxError;
+pub use pad::{WebRtcReduxPad, TARGET_BITRATE_EVENT};
+pub use retransmission::MaxAgeBuilder;
+pub use sender::{add_header_extension_meta, DroppedFrames, TrackMode, HEADER_EXTENSION_META, MAX_ONE_BYTE_EXTENSION_SIZE};
 use tokio::runtime::Handle;
 use webrtc::data_channel::RTCDataChannel;
 use webrtc::data_channel::data_channel_init::RTCDataChannelInit;
 use webrtc::ice_transport::ice_gatherer::OnICEGathererStateChangeHdlrFn;
 use webrtc::ice_transport::ice_gatherer::OnLocalCandidateHdlrFn;
 use webrtc::peer_connection::OnICEConnectionStateChangeHdlrFn;
+use webrtc::peer_connection::OnDataChannelHdlrFn;
 use webrtc::peer_connection::OnNegotiationNeededHdlrFn;
 use webrtc::peer_connection::OnPeerConnectionStateChangeHdlrFn;
+use webrtc::peer_connection::OnTrackHdlrFn;
 use webrtc::rtp_transceiver::RTCRtpTransceiverInit;
 use webrtc::rtp_transceiver::rtp_codec::RTPCodecType;
 
@@ -37,101 +58,484 @@ impl Default for WebRtcRedux {
 unsafe impl Send for WebRtcRedux {}
 unsafe impl Sync for WebRtcRedux {}
 
-//TODO: Add signal for those methods for compatibility with other programing languages
+//TODO: Add signals for the remaining methods for compatibility with other programing languages, negotiation is
+// covered by the create-offer, create-answer, set-local-description, set-remote-description and add-ice-candidate
+// action signals
 impl WebRtcRedux {
+    /// Starts configuring an element whose options are validated up front, see [`WebRtcReduxBuilder`].
+    pub fn builder() -> WebRtcReduxBuilder {
+        WebRtcReduxBuilder::new()
+    }
+
+    /// Replaces the whole peer connection configuration, including anything set through `add_ice_servers` or
+    /// `set_bundle_policy` so far, must be set before starting. Later granular setters apply on top of it.
+    pub fn set_configuration(&self, config: RTCConfiguration) {
+        imp::WebRtcRedux::from_instance(self).set_configuration(config);
+    }
+
     pub fn add_ice_servers(&self, ice_servers: Vec<RTCIceServer>) {
         imp::WebRtcRedux::from_instance(self).add_ice_servers(ice_servers);
     }
 
+    /// Replaces the ICE servers, e.g. to rotate expired TURN credentials.
+    ///
+    /// webrtc-rs can't reconfigure a running peer connection, so once started the servers are kept for the
+    /// connection created on the next start instead of the current ICE restart.
+    pub async fn set_ice_servers(&self, ice_servers: Vec<RTCIceServer>) {
+        imp::WebRtcRedux::from_instance(self).set_ice_servers(ice_servers).await;
+    }
+
+    /// Also available as the `bundle-policy` property, ICE servers can be set with the `stun-server` and `turn-server`
+    /// properties as well.
     pub fn set_bundle_policy(&self, bundle_policy: RTCBundlePolicy) {
         imp::WebRtcRedux::from_instance(self).set_bundle_policy(bundle_policy);
     }
 
-    pub fn set_stream_id(&self, pad_name: &str, stream_id: &str) -> Result<(), ErrorMessage> {
+    pub fn set_candidate_type_filter(&self, types: Vec<RTCIceCandidateType>) {
+        imp::WebRtcRedux::from_instance(self).set_candidate_type_filter(types);
+    }
+
+    /// Advertises `cname` for every local source instead of the track's stream id, must be set before starting.
+    ///
+    /// Only the `a=ssrc` lines of generated descriptions carry it. webrtc-rs sends no RTCP SDES packets, so the remote
+    /// only learns the cname through signaling.
+    pub fn set_cname(&self, cname: &str) {
+        imp::WebRtcRedux::from_instance(self).set_cname(cname);
+    }
+
+    /// Enables toggling in-band FEC on upstream Opus encoders based on the loss reported by the remote, `None` disables it
+    pub fn set_adaptive_opus_fec(&self, fec: Option<AdaptiveOpusFec>) {
+        imp::WebRtcRedux::from_instance(self).set_adaptive_opus_fec(fec);
+    }
+
+    /// Restarts ICE when the connection stays `Disconnected` longer than the grace period, `None` disables it. Must be set before starting.
+    ///
+    /// Every attempt posts a `webrtcredux-ice-reconnect` element message and hands its offer to [`WebRtcRedux::on_reconnect_offer`].
+    pub fn set_auto_reconnect(&self, policy: Option<AutoReconnect>) {
+        imp::WebRtcRedux::from_instance(self).set_auto_reconnect(policy);
+    }
+
+    /// Links pads to the sink returned by `factory` instead of the internal sender, e.g. an instrumented sink in tests.
+    ///
+    /// Tracks are still negotiated for these pads, but nothing is written to them. Takes effect when the element goes
+    /// to PAUSED, `None` restores the internal sender.
+    pub fn set_sender_factory(&self, factory: Option<SenderFactoryFn>) {
+        imp::WebRtcRedux::from_instance(self).set_sender_factory(factory);
+    }
+
+    /// Called with the ICE restart offer of every automatic reconnection attempt, which the application has to send to the remote.
+    pub fn on_reconnect_offer(&self, f: OnReconnectOfferHdlrFn) {
+        imp::WebRtcRedux::from_instance(self).on_reconnect_offer(f);
+    }
+
+    /// Enables or disables offering `feedback` (e.g. transport-cc) for every codec of `kind`, must be set before starting.
+    pub fn set_rtcp_feedback(&self, kind: RTPCodecType, feedback: RTCPFeedback, enabled: bool) {
+        imp::WebRtcRedux::from_instance(self).set_rtcp_feedback(kind, feedback, enabled);
+    }
+
+    /// Negotiates a custom header extension, values are attached to buffers with [`add_header_extension_meta`]. Must be called before starting.
+    pub fn register_header_extension(&self, uri: &str, kind: RTPCodecType) {
+        imp::WebRtcRedux::from_instance(self).register_header_extension(uri, kind);
+    }
+
+    /// Refuses pad requests of the given kind while disabled, pads that already exist are kept.
+    pub fn set_media_enabled(&self, kind: RTPCodecType, enabled: bool) {
+        imp::WebRtcRedux::from_instance(self).set_media_enabled(kind, enabled);
+    }
+
+    /// Advertises `address` in the origin and connection lines of generated offers and answers, e.g. a public IP behind NAT.
+    pub fn set_advertised_address(&self, address: Option<IpAddr>) {
+        imp::WebRtcRedux::from_instance(self).set_advertised_address(address);
+    }
+
+    /// Writes `(start, stop)` as NTP seconds into the `t=` line of generated offers and answers, `None` keeps `t=0 0`.
+    pub fn set_session_timing(&self, timing: Option<(usize, usize)>) {
+        imp::WebRtcRedux::from_instance(self).set_session_timing(timing);
+    }
+
+    /// Stamps abs-send-time and transport-cc header extensions on outgoing packets so the remote can estimate bandwidth, must be set before starting.
+    pub fn set_send_side_bwe(&self, enabled: bool) {
+        imp::WebRtcRedux::from_instance(self).set_send_side_bwe(enabled);
+    }
+
+    /// Picks the bandwidth estimation negotiated with the remote, overriding `set_send_side_bwe` and
+    /// `set_transport_cc_feedback`, must be set before starting. See `CongestionControl` for the tradeoffs.
+    ///
+    /// `None`, the default, follows those two settings, which only send transport-cc feedback for incoming streams.
+    pub fn set_congestion_control(&self, control: Option<CongestionControl>) {
+        imp::WebRtcRedux::from_instance(self).set_congestion_control(control);
+    }
+
+    /// Sends transport-cc feedback for incoming streams so the remote sender can adapt its bitrate, enabled by default, must be set before starting.
+    pub fn set_transport_cc_feedback(&self, enabled: bool) {
+        imp::WebRtcRedux::from_instance(self).set_transport_cc_feedback(enabled);
+    }
+
+    /// Ignores NACKs for packets sent more than `age` milliseconds ago, 0 disables the limit, must be set before starting.
+    pub fn set_max_retransmission_age(&self, age: u32) {
+        imp::WebRtcRedux::from_instance(self).set_max_retransmission_age(age);
+    }
+
+    /// Runs a lite ICE agent for endpoints with a public address, advertising `a=ice-lite` and only offering host candidates, must be set before starting.
+    pub fn set_ice_lite(&self, enabled: bool) {
+        imp::WebRtcRedux::from_instance(self).set_ice_lite(enabled);
+    }
+
+    /// Adds a custom `a=` line to every description generated from now on, for extensions the element doesn't model.
+    ///
+    /// Attributes are appended after the session or media section attributes in the order they were added.
+    pub fn add_sdp_attribute(&self, target: SdpTarget, key: &str, value: Option<String>) {
+        imp::WebRtcRedux::from_instance(self).add_sdp_attribute(target, key, value);
+    }
+
+    /// Removes the attributes added with `add_sdp_attribute`
+    pub fn clear_sdp_attributes(&self) {
+        imp::WebRtcRedux::from_instance(self).clear_sdp_attributes();
+    }
+
+    /// Calls `f` on every offer and answer the element generates, and on `local_description`, for rewrites that have no
+    /// dedicated setter such as bitrate caps or codec filtering.
+    ///
+    /// `f` runs after the element's own rewrites, right before the description is returned. The local peer connection
+    /// never sees any of these rewrites (rtcp-rsize, cname, timing, custom attributes or this transform): webrtc-rs only
+    /// accepts the text it generated, so `set_local_description` hands it that one. They only change what is sent to
+    /// the remote.
+    pub fn set_sdp_transform(&self, f: impl Fn(&mut SDP) + Send + Sync + 'static) {
+        imp::WebRtcRedux::from_instance(self).set_sdp_transform(Arc::new(f));
+    }
+
+    /// Removes the transform set with `set_sdp_transform`
+    pub fn clear_sdp_transform(&self) {
+        imp::WebRtcRedux::from_instance(self).clear_sdp_transform();
+    }
+
+    /// Offers reduced-size RTCP (`a=rtcp-rsize`) in descriptions generated from now on if `Some(true)`, or strips it for
+    /// endpoints that mishandle it if `Some(false)`. `None` keeps what webrtc-rs generates.
+    pub fn set_rtcp_rsize(&self, enabled: Option<bool>) {
+        imp::WebRtcRedux::from_instance(self).set_rtcp_rsize(enabled);
+    }
+
+    /// Stamps the ssrc-audio-level header extension on audio packets for active speaker detection, must be set before starting.
+    ///
+    /// Levels are measured from the raw input of pads with an inserted encoder (see `set_auto_encoder`), this costs a pass
+    /// over every audio buffer and is disabled by default.
+    pub fn set_audio_level(&self, enabled: bool) {
+        imp::WebRtcRedux::from_instance(self).set_audio_level(enabled);
+    }
+
+    /// Keeps the peer connection, its tracks and the sender elements alive when the element goes back to Ready or Null,
+    /// so it can be restarted without renegotiating. The connection then has to be closed with `stop_connection`.
+    pub fn set_preserve_connection(&self, enabled: bool) {
+        imp::WebRtcRedux::from_instance(self).set_preserve_connection(enabled);
+    }
+
+    /// Writes encoded frames at most once per frame duration, so several frames an encoder outputs at once are spread
+    /// out again. The packets of a single frame are still sent back to back, this doesn't pace packets on the wire.
+    /// Disabled by default for the lowest latency, must be set before starting. Pads in RTP mode are not paced.
+    pub fn set_frame_pacing(&self, enabled: bool) {
+        imp::WebRtcRedux::from_instance(self).set_frame_pacing(enabled);
+    }
+
+    /// Posts a `webrtcredux-dropped-frames` element message with the `pad` name and its `keyframes` and `delta-frames`
+    /// totals whenever a sender drops a frame, see `dropped_frames`. Disabled by default, must be set before starting.
+    pub fn set_post_dropped_frames(&self, enabled: bool) {
+        imp::WebRtcRedux::from_instance(self).set_post_dropped_frames(enabled);
+    }
+
+    /// Hints the remote jitter buffer through the playout-delay header extension on video tracks, must be set before starting.
+    pub fn set_playout_delay(&self, delay: Option<PlayoutDelay>) {
+        imp::WebRtcRedux::from_instance(self).set_playout_delay(delay);
+    }
+
+    pub fn set_stream_id(&self, pad_name: &str, stream_id: &str) -> Result<(), WebRtcReduxError> {
         imp::WebRtcRedux::from_instance(self).set_stream_id(pad_name, stream_id)
     }
 
+    /// Sets the ordered list of codecs offered for the pad's transceiver so the remote can fall back to them.
+    ///
+    /// Must be called before the pad receives caps. The track itself still sends the single codec produced by
+    /// the upstream encoder, so that codec has to be part of the list and the remote has to accept it.
+    pub fn set_codec_preferences(&self, pad_name: &str, codecs: Vec<RTCRtpCodecParameters>) -> Result<(), WebRtcReduxError> {
+        imp::WebRtcRedux::from_instance(self).set_codec_preferences(pad_name, codecs)
+    }
+
+    /// Codecs new pads of `kind` prefer until [`WebRtcRedux::set_codec_preferences`] overrides them, empty keeps the
+    /// MediaEngine's order. Pads that already exist keep theirs.
+    pub fn set_default_codec_preferences(&self, kind: RTPCodecType, codecs: Vec<RTCRtpCodecParameters>) {
+        imp::WebRtcRedux::from_instance(self).set_default_codec_preferences(kind, codecs);
+    }
+
+    /// Overrides the H264 level_idc (e.g. `0x28` for level 4.0) advertised for the pad instead of deriving it from the caps.
+    ///
+    /// Must be called before the pad receives caps.
+    pub fn set_h264_level(&self, pad_name: &str, level: u8) -> Result<(), WebRtcReduxError> {
+        imp::WebRtcRedux::from_instance(self).set_h264_level(pad_name, level)
+    }
+
+    /// Advertises the role of the pad's stream with `a=content` (RFC 4796), e.g. `slides` for screen sharing or `main`
+    /// for a camera, in descriptions generated from now on. The remote's roles can be read with `SDP::content`.
+    pub fn set_content(&self, pad_name: &str, content: Option<&str>) -> Result<(), WebRtcReduxError> {
+        imp::WebRtcRedux::from_instance(self).set_content(pad_name, content)
+    }
+
+    /// Stops sending the pad's media while `muted`, without renegotiating. Buffers are discarded until the pad is
+    /// unmuted, which asks upstream for a keyframe on video pads so the remote can decode again right away.
+    pub fn set_pad_muted(&self, pad_name: &str, muted: bool) -> Result<(), WebRtcReduxError> {
+        imp::WebRtcRedux::from_instance(self).set_pad_muted(pad_name, muted)
+    }
+
+    /// Asks the encoder upstream of the video pad for a keyframe right away, e.g. when a new viewer joins. Returns whether
+    /// upstream handled the request. PLI and FIR feedback from the remote is turned into the same request.
+    pub fn request_keyframe(&self, pad_name: &str) -> Result<bool, WebRtcReduxError> {
+        imp::WebRtcRedux::from_instance(self).request_keyframe(pad_name)
+    }
+
+    /// Frames the pad's sender discarded so far, split by keyframes and delta frames. Frames are discarded while the pad
+    /// is muted and when a flush interrupts them waiting for their pacing slot. Also part of `track_stats`.
+    pub fn dropped_frames(&self, pad_name: &str) -> Result<DroppedFrames, WebRtcReduxError> {
+        imp::WebRtcRedux::from_instance(self).dropped_frames(pad_name)
+    }
+
+    /// Scales the pad's video through the steps of `ladder` as the remote's bandwidth estimate drops, and back up once it
+    /// recovers. The estimate comes from the remote's REMB feedback, so this requires `CongestionControl::Remb`.
+    ///
+    /// Pads with an inserted encoder (see `set_auto_encoder`) are scaled in front of it. Every change is also posted as a
+    /// `webrtcredux-resolution` element message with the pad, width, height and bitrate, for applications that encode
+    /// themselves. Must be called before the element goes to PAUSED.
+    pub fn set_resolution_ladder(&self, pad_name: &str, ladder: Option<ResolutionLadder>) -> Result<(), WebRtcReduxError> {
+        imp::WebRtcRedux::from_instance(self).set_resolution_ladder(pad_name, ladder)
+    }
+
+    /// Offers `fallbacks` after the codec of the pad's inserted encoder, the encoder is created for the first codec the
+    /// remote accepts once a remote description is set. Input is held back until then.
+    ///
+    /// Requires an encoder set with `set_auto_encoder`, must be called before the element goes to PAUSED.
+    pub fn set_codec_fallbacks(&self, pad_name: &str, fallbacks: &[EncoderCodec]) -> Result<(), WebRtcReduxError> {
+        imp::WebRtcRedux::from_instance(self).set_codec_fallbacks(pad_name, fallbacks)
+    }
+
+    /// Inserts an encoder in front of the pad so raw `video/x-raw` or `audio/x-raw` can be linked directly, `None` expects encoded input.
+    ///
+    /// Must be called before the element goes to PAUSED.
+    pub fn set_auto_encoder(&self, pad_name: &str, encoder: Option<AutoEncoder>) -> Result<(), WebRtcReduxError> {
+        imp::WebRtcRedux::from_instance(self).set_auto_encoder(pad_name, encoder)
+    }
+
+    /// Chooses between hardware and software encoders for pads with an inserted encoder, software by default.
+    ///
+    /// With `EncoderPreference::Hardware` the NVENC, VA, VA-API, Media SDK, VideoToolbox and V4L2 encoders of the codec
+    /// are tried in that order, the first one that is installed and opens its device at Ready is used. The software
+    /// encoder is inserted when none does, Opus always uses the software encoder. Must be set before the element goes to PAUSED.
+    pub fn set_encoder_preference(&self, preference: EncoderPreference) {
+        imp::WebRtcRedux::from_instance(self).set_encoder_preference(preference);
+    }
+
+    /// Defines what happens when upstream switches a pad with a track to another codec, rejected by default.
+    ///
+    /// `CodecChangePolicy::Recreate` removes the track and adds one with the new codec, `on_negotiation_needed` then
+    /// fires and the remote only receives the pad again after renegotiating.
+    pub fn set_codec_change_policy(&self, policy: CodecChangePolicy) {
+        imp::WebRtcRedux::from_instance(self).set_codec_change_policy(policy);
+    }
+
     pub async fn add_transceiver(
         &self,
         codec_type: RTPCodecType,
-        init_params: &[RTCRtpTransceiverInit]) -> Result<Arc<RTCRtpTransceiver>, ErrorMessage>
+        init_params: &[RTCRtpTransceiverInit]) -> Result<Arc<RTCRtpTransceiver>, WebRtcReduxError>
     {
         imp::WebRtcRedux::from_instance(self)
             .add_transceiver_from_kind(codec_type, init_params)
             .await
     }
 
+    /// Returns the mid and negotiated direction of every transceiver, which may differ from the requested direction
+    pub async fn transceiver_directions(&self) -> Result<Vec<(String, RTCRtpTransceiverDirection)>, WebRtcReduxError> {
+        imp::WebRtcRedux::from_instance(self).transceiver_directions().await
+    }
+
+    /// Returns the SSRC of the pad's outgoing RTP stream, `None` until the pad's track has been created
+    pub async fn track_ssrc(&self, pad_name: &str) -> Result<Option<u32>, WebRtcReduxError> {
+        imp::WebRtcRedux::from_instance(self).track_ssrc(pad_name).await
+    }
+
     pub async fn create_offer(
         &self,
         options: Option<RTCOfferOptions>,
-    ) -> Result<SDP, ErrorMessage> {
+    ) -> Result<SDP, WebRtcReduxError> {
         imp::WebRtcRedux::from_instance(self)
             .create_offer(options)
             .await
     }
 
-    pub async fn gathering_complete_promise(&self) -> Result<tokio::sync::mpsc::Receiver<()>, ErrorMessage> {
+    /// Mid of the media section carrying the pad's track, `None` until the track exists and has been offered or answered.
+    pub async fn mid_for_pad(&self, pad_name: &str) -> Result<Option<String>, WebRtcReduxError> {
+        imp::WebRtcRedux::from_instance(self).mid_for_pad(pad_name).await
+    }
+
+    /// Name, codec, stream id, mid and track state of every sink pad, sorted by name. Works without a running peer
+    /// connection, mids are only filled in once one exists.
+    pub async fn pads_info(&self) -> Vec<PadInfo> {
+        imp::WebRtcRedux::from_instance(self).pads_info().await
+    }
+
+    /// Sending statistics of every pad whose track has been created, keyed by pad name. Loss, round-trip time and
+    /// jitter are only known once the remote sent receiver reports.
+    pub async fn track_stats(&self) -> Result<HashMap<String, TrackStats>, WebRtcReduxError> {
+        imp::WebRtcRedux::from_instance(self).track_stats().await
+    }
+
+    /// Every statistic webrtc-rs collects, like candidate pairs, codecs and RTP streams. Also available as the `stats`
+    /// property, with a structure per report. Reading the property blocks the calling thread until webrtc-rs gathered
+    /// the report, so it shouldn't be read from a streaming thread or the element's runtime.
+    pub async fn get_stats(&self) -> Result<StatsReport, WebRtcReduxError> {
+        imp::WebRtcRedux::from_instance(self).get_stats().await
+    }
+
+    /// DTLS role the local endpoint ended up with, fails with `WebRtcReduxError::DtlsRoleNotNegotiated` until an
+    /// offer and answer have been applied
+    pub async fn dtls_role(&self) -> Result<DtlsRole, WebRtcReduxError> {
+        imp::WebRtcRedux::from_instance(self).dtls_role().await
+    }
+
+    pub async fn gathering_complete_promise(&self) -> Result<tokio::sync::mpsc::Receiver<()>, WebRtcReduxError> {
         imp::WebRtcRedux::from_instance(self).gathering_complete_promise().await
     }
 
+    /// Fails with `WebRtcReduxError::NoRemoteOffer` unless a remote offer has been set
     pub async fn create_answer(
         &self,
         options: Option<RTCAnswerOptions>
-    ) -> Result<SDP, ErrorMessage> {
+    ) -> Result<SDP, WebRtcReduxError> {
         imp::WebRtcRedux::from_instance(self)
             .create_answer(options)
             .await
     }
 
-    pub async fn local_description(&self) -> Result<Option<SDP>, ErrorMessage> {
+    /// Creates an offer, sets it as local description and returns it with all candidates once ICE gathering is complete,
+    /// for signaling without trickle ICE
+    pub async fn create_complete_offer(&self) -> Result<SDP, WebRtcReduxError> {
+        imp::WebRtcRedux::from_instance(self).create_complete_offer().await
+    }
+
+    /// Like `create_complete_offer` for the answer to a remote offer, fails with `WebRtcReduxError::NoRemoteOffer` without one
+    pub async fn create_complete_answer(&self) -> Result<SDP, WebRtcReduxError> {
+        imp::WebRtcRedux::from_instance(self).create_complete_answer().await
+    }
+
+    /// Creates an offer, sets it as local description and returns it without candidates, the recommended path for
+    /// low-latency signaling with trickle ICE. Every gathered candidate is then passed to `on_candidate`, followed by
+    /// `None` for end-of-candidates. Replaces any handler set with `on_ice_candidate`, the candidate type filter still applies.
+    pub async fn create_trickle_offer(&self, on_candidate: OnLocalCandidateHdlrFn) -> Result<SDP, WebRtcReduxError> {
+        imp::WebRtcRedux::from_instance(self).create_trickle_offer(on_candidate).await
+    }
+
+    /// Like `create_trickle_offer` for the answer to a remote offer, fails with `WebRtcReduxError::NoRemoteOffer` without one
+    pub async fn create_trickle_answer(&self, on_candidate: OnLocalCandidateHdlrFn) -> Result<SDP, WebRtcReduxError> {
+        imp::WebRtcRedux::from_instance(self).create_trickle_answer(on_candidate).await
+    }
+
+    pub async fn local_description(&self) -> Result<Option<SDP>, WebRtcReduxError> {
         imp::WebRtcRedux::from_instance(self).local_description().await
     }
 
-    pub async fn set_local_description(&self, sdp: &SDP, sdp_type: RTCSdpType) -> Result<(), ErrorMessage> {
+    /// Returns the local ICE `(ufrag, pwd)` for deployments that run ICE outside the element
+    pub async fn ice_credentials(&self) -> Result<(String, String), WebRtcReduxError> {
+        imp::WebRtcRedux::from_instance(self).ice_credentials().await
+    }
+
+    /// The local description exactly as held by the peer connection, without going through the SDP parser.
+    ///
+    /// Note that the candidate type filter is not applied to this string.
+    pub async fn local_description_raw(&self) -> Result<Option<String>, WebRtcReduxError> {
+        imp::WebRtcRedux::from_instance(self).local_description_raw().await
+    }
+
+    pub async fn set_local_description(&self, sdp: &SDP, sdp_type: RTCSdpType) -> Result<(), WebRtcReduxError> {
         imp::WebRtcRedux::from_instance(self)
             .set_local_description(sdp, sdp_type)
             .await
     }
 
-    pub async fn remote_description(&self) -> Result<Option<SDP>, ErrorMessage> {
+    pub async fn remote_description(&self) -> Result<Option<SDP>, WebRtcReduxError> {
         imp::WebRtcRedux::from_instance(self).remote_description().await
     }
 
-    pub async fn set_remote_description(&self, sdp: &SDP, sdp_type: RTCSdpType) -> Result<(), ErrorMessage> {
+    /// The remote description exactly as held by the peer connection, without going through the SDP parser.
+    pub async fn remote_description_raw(&self) -> Result<Option<String>, WebRtcReduxError> {
+        imp::WebRtcRedux::from_instance(self).remote_description_raw().await
+    }
+
+    pub async fn set_remote_description(&self, sdp: &SDP, sdp_type: RTCSdpType) -> Result<(), WebRtcReduxError> {
         imp::WebRtcRedux::from_instance(self)
             .set_remote_description(sdp, sdp_type)
             .await
     }
 
-    pub async fn on_negotiation_needed(&self, f: OnNegotiationNeededHdlrFn) -> Result<(), ErrorMessage>
+    /// Also fires for pads requested while the element is running, once their track has been created from the first caps
+    pub async fn on_negotiation_needed(&self, f: OnNegotiationNeededHdlrFn) -> Result<(), WebRtcReduxError>
     {
         imp::WebRtcRedux::from_instance(self)
             .on_negotiation_needed(f)
             .await
     }
 
-    pub async fn on_ice_candidate(&self, f: OnLocalCandidateHdlrFn) -> Result<(), ErrorMessage>
+    /// Whether negotiation has been needed since signaling was last stable, e.g. because tracks or transceivers were
+    /// added. Lets applications renegotiate once after several changes instead of on every `on_negotiation_needed`.
+    pub fn needs_renegotiation(&self) -> bool {
+        imp::WebRtcRedux::from_instance(self).needs_renegotiation()
+    }
+
+    /// Registers a handler for local candidates, the end of gathering is passed as `None`. Candidates are also emitted
+    /// as the `on-ice-candidate` signal and posted as a `webrtcredux-ice-candidate` element message with the
+    /// mline index, mid and candidate string. Every section is bundled, so these are the first section's.
+    pub async fn on_ice_candidate(&self, f: OnLocalCandidateHdlrFn) -> Result<(), WebRtcReduxError>
     {
         imp::WebRtcRedux::from_instance(self)
             .on_ice_candidate(f)
             .await
     }
 
-    pub async fn on_ice_gathering_state_change(&self, f: OnICEGathererStateChangeHdlrFn) -> Result<(), ErrorMessage>
+    pub async fn on_ice_gathering_state_change(&self, f: OnICEGathererStateChangeHdlrFn) -> Result<(), WebRtcReduxError>
     {
         imp::WebRtcRedux::from_instance(self)
             .on_ice_gathering_state_change(f)
             .await
     }
 
-    pub async fn on_ice_connection_state_change(&self, f: OnICEConnectionStateChangeHdlrFn) -> Result<(), ErrorMessage>
+    pub async fn on_ice_connection_state_change(&self, f: OnICEConnectionStateChangeHdlrFn) -> Result<(), WebRtcReduxError>
     {
         imp::WebRtcRedux::from_instance(self)
             .on_ice_connection_state_change(f)
             .await
     }
 
-    pub fn on_peer_connection_state_change(&self, f: OnPeerConnectionStateChangeHdlrFn) -> Result<(), ErrorMessage>
+    /// Registers a handler for tracks added by the remote peer.
+    ///
+    /// Every remote track is also exposed on a `src_%u` sometimes pad pushing its RTP packets with caps for a depayloader,
+    /// so the handler shouldn't read from the track itself. The pads are removed once the connection stops.
+    ///
+    /// For simulcast the layers of one source arrive as separate tracks, use `TrackRemote::rid()` to tell them apart.
+    pub async fn on_track(&self, f: OnTrackHdlrFn) -> Result<(), WebRtcReduxError>
+    {
+        imp::WebRtcRedux::from_instance(self)
+            .on_track(f)
+            .await
+    }
+
+    /// Connects to the `on-track` signal, emitted with the `src_%u` pad of every remote track once it was added, its RTP
+    /// caps, SSRC and RID. Unlike `on_track` this is available before starting and from other languages.
+    pub fn connect_on_track<F: Fn(&Self, &gst::Pad, &gst::Caps, u32, &str) + Send + Sync + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect("on-track", false, move |values| {
+            let element = values[0].get::<Self>().unwrap();
+            let pad = values[1].get::<gst::Pad>().unwrap();
+            let caps = values[2].get::<gst::Caps>().unwrap();
+            let rid = values[4].get::<String>().unwrap();
+            f(&element, &pad, &caps, values[3].get().unwrap(), &rid);
+            None
+        })
+    }
+
+    pub fn on_peer_connection_state_change(&self, f: OnPeerConnectionStateChangeHdlrFn) -> Result<(), WebRtcReduxError>
     {
         imp::WebRtcRedux::from_instance(self)
             .on_peer_connection_state_change(f)
@@ -140,18 +544,39 @@ impl WebRtcRedux {
     pub async fn add_ice_candidate(
         &self,
         candidate: RTCIceCandidateInit,
-    ) -> Result<(), ErrorMessage> {
+    ) -> Result<(), WebRtcReduxError> {
         imp::WebRtcRedux::from_instance(self)
             .add_ice_candidate(candidate)
             .await
     }
 
-    pub async fn create_data_channel(&self, name: &str, init_params: Option<RTCDataChannelInit>) -> Result<Arc<RTCDataChannel>, ErrorMessage> {
+    pub async fn create_data_channel(&self, name: &str, init_params: Option<RTCDataChannelInit>) -> Result<Arc<RTCDataChannel>, WebRtcReduxError> {
         imp::WebRtcRedux::from_instance(self)
             .create_data_channel(name, init_params)
             .await
     }
 
+    /// Registers a handler for data channels opened by the remote peer, e.g. when answering an offer with an
+    /// `application` section. Can be set before starting and is kept across connections.
+    pub fn on_data_channel(&self, f: OnDataChannelHdlrFn) {
+        imp::WebRtcRedux::from_instance(self).on_data_channel(f);
+    }
+
+    /// Channels of the running connection that aren't closed yet, whether created locally or by the remote.
+    pub async fn data_channels(&self) -> Vec<DataChannelInfo> {
+        imp::WebRtcRedux::from_instance(self).data_channels().await
+    }
+
+    /// Creates the peer connection, done automatically when going from Null to Ready.
+    pub fn start_connection(&self) -> Result<(), WebRtcReduxError> {
+        imp::WebRtcRedux::from_instance(self).start_connection()
+    }
+
+    /// Closes the peer connection, done automatically when going from Ready to Null. Settings can be changed again afterwards.
+    pub fn stop_connection(&self) -> Result<(), WebRtcReduxError> {
+        imp::WebRtcRedux::from_instance(self).stop_connection()
+    }
+
     pub fn set_tokio_runtime(&self, handle: Handle) {
         imp::WebRtcRedux::from_instance(self).set_tokio_runtime(handle);
     }
@@ -159,6 +584,12 @@ impl WebRtcRedux {
     pub async fn wait_for_all_tracks(&self) {
         imp::WebRtcRedux::from_instance(self).wait_for_all_tracks().await;
     }
+
+    /// Resolves once ICE is connected or completed, which can be before the whole peer connection is. Fails with
+    /// `WebRtcReduxError::IceConnectionFailed` if ICE fails, disconnects or closes first.
+    pub async fn wait_for_ice_connected(&self) -> Result<(), WebRtcReduxError> {
+        imp::WebRtcRedux::from_instance(self).wait_for_ice_connected().await
+    }
 }
 
 pub fn register(plugin: &gst::Plugin) -> Result<(), glib::BoolError> {
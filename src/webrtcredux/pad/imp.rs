@@ -0,0 +1,104 @@
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use gst::glib;
+use gst::prelude::*;
+use gst::subclass::prelude::*;
+use gst::warning;
+use once_cell::sync::Lazy;
+
+use crate::webrtcredux::{TrackMode, CAT};
+
+/// Sink pad of the element, exposes what the network allows for its stream
+#[derive(Default)]
+pub struct WebRtcReduxPad {
+    target_bitrate: Mutex<u32>,
+}
+
+impl WebRtcReduxPad {
+    /// Returns whether the bitrate changed
+    pub fn set_target_bitrate(&self, bitrate: u32) -> bool {
+        let mut target_bitrate = self.target_bitrate.lock().unwrap();
+        let changed = *target_bitrate != bitrate;
+        *target_bitrate = bitrate;
+        changed
+    }
+
+    fn element(&self) -> Option<crate::webrtcredux::WebRtcRedux> {
+        self.obj().parent().and_then(|parent| parent.downcast().ok())
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for WebRtcReduxPad {
+    const NAME: &'static str = "WebRtcReduxPad";
+    type Type = super::WebRtcReduxPad;
+    type ParentType = gst::GhostPad;
+}
+
+impl ObjectImpl for WebRtcReduxPad {
+    fn properties() -> &'static [glib::ParamSpec] {
+        static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+            vec![
+                glib::ParamSpecUInt::builder("target-bitrate")
+                    .nick("Target Bitrate")
+                    .blurb("Bitrate in bit/s the remote's bandwidth estimate leaves for this stream, 0 until one arrived")
+                    .read_only()
+                    .build(),
+                glib::ParamSpecString::builder("track-mode")
+                    .nick("Track Mode")
+                    .blurb("Whether the pad takes encoded frames (sample) or packets from a payloader (rtp), follows the first caps unless set")
+                    .default_value(Some("sample"))
+                    .build(),
+            ]
+        });
+
+        PROPERTIES.as_ref()
+    }
+
+    fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+        match pspec.name() {
+            "track-mode" => {
+                let obj = self.obj();
+                let mode = value.get::<Option<String>>().expect("type checked upstream");
+                let mode = match mode.as_deref().map(TrackMode::from_str) {
+                    Some(Ok(mode)) => mode,
+                    _ => {
+                        warning!(CAT, obj: &*obj, "Ignoring unknown track mode {:?}", mode);
+                        return;
+                    }
+                };
+                match self.element() {
+                    Some(element) => {
+                        if let Err(e) = element.imp().set_track_mode(&obj.name(), mode) {
+                            warning!(CAT, obj: &*obj, "Failed to set track mode: {}", e);
+                        }
+                    }
+                    None => warning!(CAT, obj: &*obj, "Pad isn't part of an element, ignoring track mode"),
+                }
+            }
+            _ => unimplemented!(),
+        }
+    }
+
+    fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        match pspec.name() {
+            "target-bitrate" => self.target_bitrate.lock().unwrap().to_value(),
+            "track-mode" => self
+                .element()
+                .and_then(|element| element.imp().track_mode(&self.obj().name()))
+                .unwrap_or_default()
+                .to_string()
+                .to_value(),
+            _ => unimplemented!(),
+        }
+    }
+}
+
+impl GstObjectImpl for WebRtcReduxPad {}
+
+impl PadImpl for WebRtcReduxPad {}
+
+impl ProxyPadImpl for WebRtcReduxPad {}
+
+impl GhostPadImpl for WebRtcReduxPad {}
@@ -0,0 +1,52 @@
+use gst::glib;
+use gst::prelude::*;
+use gst::subclass::prelude::ObjectSubclassExt;
+
+use crate::webrtcredux::TrackMode;
+
+mod imp;
+
+/// Name of the custom upstream event carrying a new `target-bitrate` in its `bitrate` field
+pub const TARGET_BITRATE_EVENT: &str = "webrtcredux-target-bitrate";
+
+glib::wrapper! {
+    pub struct WebRtcReduxPad(ObjectSubclass<imp::WebRtcReduxPad>) @extends gst::GhostPad, gst::ProxyPad, gst::Pad, gst::Object;
+}
+
+unsafe impl Send for WebRtcReduxPad {}
+unsafe impl Sync for WebRtcReduxPad {}
+
+impl WebRtcReduxPad {
+    /// Bitrate in bit/s the remote's REMB estimate leaves for the pad's stream, 0 until an estimate arrived. Remotes
+    /// only sending transport-cc feedback never set it, webrtc-rs has no estimator of its own.
+    /// Also available as the `target-bitrate` property, which notifies on every change.
+    pub fn target_bitrate(&self) -> u32 {
+        self.property("target-bitrate")
+    }
+
+    /// Whether the pad takes encoded frames or `application/x-rtp` packets from a payloader. Also available as the
+    /// `track-mode` property, as `sample` or `rtp`.
+    pub fn track_mode(&self) -> TrackMode {
+        self.property::<String>("track-mode").parse().unwrap_or_default()
+    }
+
+    /// Chooses the track mode, without it the mode follows the first caps the pad receives so payloaders can be
+    /// linked directly.
+    ///
+    /// Only caps of the chosen mode are negotiated on the pad, must be set before the pad receives caps, later
+    /// changes are ignored with a warning. Only the media packets of the codec are supported: every packet is
+    /// rewritten to the SSRC and payload type of the track, so FEC or RTX packets, e.g. from `rtpulpfecenc`, can't be
+    /// sent through the pad.
+    pub fn set_track_mode(&self, mode: TrackMode) {
+        self.set_property("track-mode", mode.to_string());
+    }
+
+    /// Updates `target-bitrate`, returns whether it changed
+    pub(crate) fn set_target_bitrate(&self, bitrate: u32) -> bool {
+        let changed = imp::WebRtcReduxPad::from_instance(self).set_target_bitrate(bitrate);
+        if changed {
+            self.notify("target-bitrate");
+        }
+        changed
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use gst::{debug, trace};
+use interceptor::stream_info::StreamInfo;
+use interceptor::{Attributes, Interceptor, InterceptorBuilder, RTCPReader, RTCPWriter, RTPReader, RTPWriter};
+use webrtc::rtcp::transport_feedbacks::transport_layer_nack::{nack_pairs_from_sequence_numbers, TransportLayerNack};
+
+use crate::webrtcredux::CAT;
+
+/// Send times of the recent packets of each local stream, oldest first
+type SendTimes = Arc<Mutex<HashMap<u32, VecDeque<(u16, Instant)>>>>;
+
+/// Builds an interceptor removing packets sent more than `max_age` ago from incoming NACKs, which has to be registered
+/// before the NACK responder so it sees feedback first
+pub struct MaxAgeBuilder {
+    max_age: Duration,
+}
+
+impl MaxAgeBuilder {
+    pub fn new(max_age: Duration) -> Self {
+        MaxAgeBuilder { max_age }
+    }
+}
+
+impl InterceptorBuilder for MaxAgeBuilder {
+    fn build(&self, _id: &str) -> Result<Arc<dyn Interceptor + Send + Sync>, interceptor::Error> {
+        Ok(Arc::new(MaxAge {
+            max_age: self.max_age,
+            send_times: Default::default(),
+        }))
+    }
+}
+
+/// Removes packets sent longer than `max_age` ago from incoming NACKs, so they aren't retransmitted after they are
+/// already too late for playout
+struct MaxAge {
+    max_age: Duration,
+    send_times: SendTimes,
+}
+
+#[async_trait]
+impl Interceptor for MaxAge {
+    async fn bind_rtcp_reader(&self, reader: Arc<dyn RTCPReader + Send + Sync>) -> Arc<dyn RTCPReader + Send + Sync> {
+        Arc::new(MaxAgeRtcpReader {
+            parent: reader,
+            max_age: self.max_age,
+            send_times: self.send_times.clone(),
+        })
+    }
+
+    async fn bind_rtcp_writer(&self, writer: Arc<dyn RTCPWriter + Send + Sync>) -> Arc<dyn RTCPWriter + Send + Sync> {
+        writer
+    }
+
+    async fn bind_local_stream(&self, info: &StreamInfo, writer: Arc<dyn RTPWriter + Send + Sync>) -> Arc<dyn RTPWriter + Send + Sync> {
+        self.send_times.lock().unwrap().insert(info.ssrc, VecDeque::new());
+        Arc::new(MaxAgeRtpWriter {
+            parent: writer,
+            ssrc: info.ssrc,
+            max_age: self.max_age,
+            send_times: self.send_times.clone(),
+        })
+    }
+
+    async fn unbind_local_stream(&self, info: &StreamInfo) {
+        self.send_times.lock().unwrap().remove(&info.ssrc);
+    }
+
+    async fn bind_remote_stream(&self, _info: &StreamInfo, reader: Arc<dyn RTPReader + Send + Sync>) -> Arc<dyn RTPReader + Send + Sync> {
+        reader
+    }
+
+    async fn unbind_remote_stream(&self, _info: &StreamInfo) {}
+
+    async fn close(&self) -> Result<(), interceptor::Error> {
+        Ok(())
+    }
+}
+
+/// Records when each packet was first sent
+struct MaxAgeRtpWriter {
+    parent: Arc<dyn RTPWriter + Send + Sync>,
+    ssrc: u32,
+    max_age: Duration,
+    send_times: SendTimes,
+}
+
+#[async_trait]
+impl RTPWriter for MaxAgeRtpWriter {
+    async fn write(&self, pkt: &webrtc::rtp::packet::Packet, attributes: &Attributes) -> Result<usize, interceptor::Error> {
+        if let Some(times) = self.send_times.lock().unwrap().get_mut(&self.ssrc) {
+            let now = Instant::now();
+            let seq = pkt.header.sequence_number;
+            // Retransmissions pass through here as well and must not refresh the send time
+            let is_new = times.back().map_or(true, |(last, _)| (seq.wrapping_sub(*last) as i16) > 0);
+            if is_new {
+                times.push_back((seq, now));
+            }
+            while times.front().is_some_and(|(_, sent)| now.duration_since(*sent) > self.max_age) {
+                times.pop_front();
+            }
+        }
+
+        self.parent.write(pkt, attributes).await
+    }
+}
+
+/// Rewrites NACKs to only request packets that are still young enough
+struct MaxAgeRtcpReader {
+    parent: Arc<dyn RTCPReader + Send + Sync>,
+    max_age: Duration,
+    send_times: SendTimes,
+}
+
+impl MaxAgeRtcpReader {
+    /// Returns the filtered compound packet if any NACK lost entries
+    fn filter(&self, mut data: &[u8]) -> Option<Bytes> {
+        let mut packets = webrtc::rtcp::packet::unmarshal(&mut data).ok()?;
+        let send_times = self.send_times.lock().unwrap();
+        let now = Instant::now();
+
+        let mut changed = false;
+        for packet in packets.iter_mut() {
+            let nack = match packet.as_any().downcast_ref::<TransportLayerNack>() {
+                Some(nack) => nack,
+                None => continue,
+            };
+            let times = match send_times.get(&nack.media_ssrc) {
+                Some(times) => times,
+                None => continue,
+            };
+
+            let requested = nack.nacks.iter().flat_map(|pair| pair.packet_list()).collect::<Vec<_>>();
+            let young = requested
+                .iter()
+                .copied()
+                .filter(|seq| times.iter().any(|(sent_seq, sent)| sent_seq == seq && now.duration_since(*sent) <= self.max_age))
+                .collect::<Vec<_>>();
+            if young.len() == requested.len() {
+                continue;
+            }
+
+            trace!(CAT, "Ignoring NACK for {} packets older than {} ms", requested.len() - young.len(), self.max_age.as_millis());
+            changed = true;
+            *packet = Box::new(TransportLayerNack {
+                sender_ssrc: nack.sender_ssrc,
+                media_ssrc: nack.media_ssrc,
+                nacks: nack_pairs_from_sequence_numbers(&young),
+            });
+        }
+
+        if !changed {
+            return None;
+        }
+        match webrtc::rtcp::packet::marshal(&packets) {
+            Ok(data) => Some(data),
+            Err(e) => {
+                debug!(CAT, "Failed to rewrite NACK: {:?}", e);
+                None
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl RTCPReader for MaxAgeRtcpReader {
+    async fn read(&self, buf: &mut [u8], attributes: &Attributes) -> Result<(usize, Attributes), interceptor::Error> {
+        let (n, attributes) = self.parent.read(buf, attributes).await?;
+
+        // A NACK without packets left is still valid, it just makes the responder do nothing
+        Ok(match self.filter(&buf[..n]) {
+            Some(filtered) if filtered.len() <= n => {
+                buf[..filtered.len()].copy_from_slice(&filtered);
+                (filtered.len(), attributes)
+            }
+            _ => (n, attributes),
+        })
+    }
+}
@@ -1,9 +1,12 @@
 use std::{
-    fmt::Debug,
+    fmt::{Debug, Display, Formatter},
     num::{IntErrorKind, ParseIntError},
     str::FromStr,
 };
 
+use webrtc::peer_connection::sdp::sdp_type::RTCSdpType;
+use webrtc::peer_connection::sdp::session_description::RTCSessionDescription;
+
 #[derive(Debug, Clone, Copy)]
 pub enum LineEnding {
     CRLF,
@@ -36,6 +39,24 @@ pub enum MediaProp {
         bandwidth: usize,
     },
     EncryptionKeys(EncryptionKeyMethod),
+    /// `a=sctp-port`, SCTP port of a data channel section
+    SctpPort(u16),
+    /// `a=max-message-size`, largest SCTP message the endpoint can receive
+    MaxMessageSize(usize),
+    /// `a=tls-id`, identifies the DTLS association (RFC 8842)
+    TlsId(String),
+    /// `a=ice-ufrag`, ICE username fragment (RFC 8839)
+    IceUfrag(String),
+    /// `a=ice-pwd`, ICE password (RFC 8839)
+    IcePwd(String),
+    /// `a=content`, role of the stream such as `slides` or `main` (RFC 4796)
+    Content(String),
+    /// `a=setup`, which endpoint initiates the DTLS connection (RFC 4145)
+    Setup(SetupRole),
+    /// `a=ts-refclk` of this section, overrides the session one (RFC 7273)
+    TsRefClk(RefClock),
+    /// `a=mediaclk` of this section (RFC 7273)
+    MediaClk(MediaClock),
     Attribute {
         key: String,
         value: Option<String>,
@@ -85,9 +106,20 @@ impl FromStr for MediaProp {
                 let tokens = value.split(':').collect::<Vec<&str>>();
 
                 Ok(if tokens.len() > 1 {
-                    MediaProp::Attribute {
-                        key: tokens[0].to_string(),
-                        value: Some(tokens[1..].join(":")),
+                    match tokens[0] {
+                        "sctp-port" => MediaProp::SctpPort(tokens[1].parse()?),
+                        "max-message-size" => MediaProp::MaxMessageSize(tokens[1].parse()?),
+                        "tls-id" => MediaProp::TlsId(tokens[1..].join(":")),
+                        "ice-ufrag" => MediaProp::IceUfrag(tokens[1..].join(":")),
+                        "ice-pwd" => MediaProp::IcePwd(tokens[1..].join(":")),
+                        "content" => MediaProp::Content(tokens[1..].join(":")),
+                        "setup" => MediaProp::Setup(SetupRole::from_str(tokens[1])?),
+                        "ts-refclk" => MediaProp::TsRefClk(RefClock::from_str(&tokens[1..].join(":"))?),
+                        "mediaclk" => MediaProp::MediaClk(MediaClock::from_str(&tokens[1..].join(":"))?),
+                        _ => MediaProp::Attribute {
+                            key: tokens[0].to_string(),
+                            value: Some(tokens[1..].join(":")),
+                        },
                     }
                 } else {
                     MediaProp::Attribute {
@@ -130,6 +162,15 @@ impl ToString for MediaProp {
                 format!("b={}:{}", r#type.to_string(), bandwidth)
             }
             MediaProp::EncryptionKeys(method) => format!("k={}", method.to_string()),
+            MediaProp::SctpPort(port) => format!("a=sctp-port:{port}"),
+            MediaProp::MaxMessageSize(size) => format!("a=max-message-size:{size}"),
+            MediaProp::TlsId(id) => format!("a=tls-id:{id}"),
+            MediaProp::IceUfrag(ufrag) => format!("a=ice-ufrag:{ufrag}"),
+            MediaProp::IcePwd(pwd) => format!("a=ice-pwd:{pwd}"),
+            MediaProp::Content(content) => format!("a=content:{content}"),
+            MediaProp::Setup(role) => format!("a=setup:{role}"),
+            MediaProp::TsRefClk(clock) => format!("a=ts-refclk:{clock}"),
+            MediaProp::MediaClk(clock) => format!("a=mediaclk:{clock}"),
             MediaProp::Attribute { key, value } => {
                 if let Some(value) = value {
                     format!("a={}:{}", key, value)
@@ -256,6 +297,77 @@ impl ToString for BandwidthType {
     }
 }
 
+/// Transport protocol of a media section, the `<proto>` of an `m=` line
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum TransportProtocol {
+    /// `UDP/TLS/RTP/SAVPF`, media over DTLS-SRTP as used by WebRTC
+    UdpTlsRtpSavpf,
+    /// `TCP/TLS/RTP/SAVPF`
+    TcpTlsRtpSavpf,
+    /// `RTP/SAVPF`
+    RtpSavpf,
+    /// `RTP/SAVP`
+    RtpSavp,
+    /// `RTP/AVPF`
+    RtpAvpf,
+    /// `RTP/AVP`
+    RtpAvp,
+    /// `UDP/DTLS/SCTP`, data channels as used by WebRTC
+    UdpDtlsSctp,
+    /// `TCP/DTLS/SCTP`
+    TcpDtlsSctp,
+    /// `DTLS/SCTP`, the legacy data channel protocol
+    DtlsSctp,
+    /// Any other protocol, kept as is
+    Other(String),
+}
+
+impl TransportProtocol {
+    /// Whether the section carries RTP media
+    pub fn is_rtp(&self) -> bool {
+        !self.is_sctp() && !matches!(self, TransportProtocol::Other(_))
+    }
+
+    /// Whether the section carries data channels
+    pub fn is_sctp(&self) -> bool {
+        matches!(self, TransportProtocol::UdpDtlsSctp | TransportProtocol::TcpDtlsSctp | TransportProtocol::DtlsSctp)
+    }
+}
+
+impl From<&str> for TransportProtocol {
+    fn from(s: &str) -> Self {
+        match s {
+            "UDP/TLS/RTP/SAVPF" => TransportProtocol::UdpTlsRtpSavpf,
+            "TCP/TLS/RTP/SAVPF" => TransportProtocol::TcpTlsRtpSavpf,
+            "RTP/SAVPF" => TransportProtocol::RtpSavpf,
+            "RTP/SAVP" => TransportProtocol::RtpSavp,
+            "RTP/AVPF" => TransportProtocol::RtpAvpf,
+            "RTP/AVP" => TransportProtocol::RtpAvp,
+            "UDP/DTLS/SCTP" => TransportProtocol::UdpDtlsSctp,
+            "TCP/DTLS/SCTP" => TransportProtocol::TcpDtlsSctp,
+            "DTLS/SCTP" => TransportProtocol::DtlsSctp,
+            _ => TransportProtocol::Other(s.to_string()),
+        }
+    }
+}
+
+impl Display for TransportProtocol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            TransportProtocol::UdpTlsRtpSavpf => "UDP/TLS/RTP/SAVPF",
+            TransportProtocol::TcpTlsRtpSavpf => "TCP/TLS/RTP/SAVPF",
+            TransportProtocol::RtpSavpf => "RTP/SAVPF",
+            TransportProtocol::RtpSavp => "RTP/SAVP",
+            TransportProtocol::RtpAvpf => "RTP/AVPF",
+            TransportProtocol::RtpAvp => "RTP/AVP",
+            TransportProtocol::UdpDtlsSctp => "UDP/DTLS/SCTP",
+            TransportProtocol::TcpDtlsSctp => "TCP/DTLS/SCTP",
+            TransportProtocol::DtlsSctp => "DTLS/SCTP",
+            TransportProtocol::Other(protocol) => protocol,
+        })
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct TimeZoneAdjustment {
     time: usize,
@@ -342,6 +454,18 @@ pub enum SdpProp {
     },
     TimeZone(Vec<TimeZoneAdjustment>),
     EncryptionKeys(EncryptionKeyMethod),
+    /// `a=identity`, WebRTC identity assertion (RFC 8827)
+    Identity(String),
+    /// `a=ice-ufrag` shared by all media sections (RFC 8839)
+    IceUfrag(String),
+    /// `a=ice-pwd` shared by all media sections (RFC 8839)
+    IcePwd(String),
+    /// `a=extmap-allow-mixed`, one-byte and two-byte header extensions may be mixed in a stream (RFC 8285)
+    ExtmapAllowMixed,
+    /// `a=ts-refclk` shared by all media sections (RFC 7273)
+    TsRefClk(RefClock),
+    /// `a=mediaclk` shared by all media sections (RFC 7273)
+    MediaClk(MediaClock),
     Attribute {
         key: String,
         value: Option<String>,
@@ -435,10 +559,19 @@ impl FromStr for SdpProp {
                 let tokens = value.split(':').collect::<Vec<&str>>();
 
                 Ok(if tokens.len() > 1 {
-                    SdpProp::Attribute {
-                        key: tokens[0].to_string(),
-                        value: Some(tokens[1..].join(":")),
+                    match tokens[0] {
+                        "identity" => SdpProp::Identity(tokens[1..].join(":")),
+                        "ice-ufrag" => SdpProp::IceUfrag(tokens[1..].join(":")),
+                        "ice-pwd" => SdpProp::IcePwd(tokens[1..].join(":")),
+                        "ts-refclk" => SdpProp::TsRefClk(RefClock::from_str(&tokens[1..].join(":"))?),
+                        "mediaclk" => SdpProp::MediaClk(MediaClock::from_str(&tokens[1..].join(":"))?),
+                        _ => SdpProp::Attribute {
+                            key: tokens[0].to_string(),
+                            value: Some(tokens[1..].join(":")),
+                        },
                     }
+                } else if value == "extmap-allow-mixed" {
+                    SdpProp::ExtmapAllowMixed
                 } else {
                     SdpProp::Attribute {
                         key: value,
@@ -471,6 +604,25 @@ impl FromStr for SdpProp {
 }
 
 impl SdpProp {
+    /// Typed protocol of a media section, `None` for other props
+    pub fn transport_protocol(&self) -> Option<TransportProtocol> {
+        match self {
+            SdpProp::Media { protocol, .. } => Some(TransportProtocol::from(protocol.as_str())),
+            _ => None,
+        }
+    }
+
+    /// Whether this is a media section disabled with port 0, sections marked `a=bundle-only` are still in use
+    pub fn is_rejected(&self) -> bool {
+        match self {
+            SdpProp::Media { ports, props, .. } => {
+                ports.first() == Some(&0)
+                    && !props.iter().any(|prop| matches!(prop, MediaProp::Attribute { key, .. } if key == "bundle-only"))
+            }
+            _ => false,
+        }
+    }
+
     fn to_string(&self, ending: LineEnding) -> String {
         // TODO: Cut down on code copying from SDPProp to MediaProp
         match self {
@@ -531,6 +683,12 @@ impl SdpProp {
                     .join(" ")
             ),
             SdpProp::EncryptionKeys(method) => format!("k={}", method.to_string()),
+            SdpProp::Identity(assertion) => format!("a=identity:{assertion}"),
+            SdpProp::IceUfrag(ufrag) => format!("a=ice-ufrag:{ufrag}"),
+            SdpProp::IcePwd(pwd) => format!("a=ice-pwd:{pwd}"),
+            SdpProp::ExtmapAllowMixed => "a=extmap-allow-mixed".to_string(),
+            SdpProp::TsRefClk(clock) => format!("a=ts-refclk:{clock}"),
+            SdpProp::MediaClk(clock) => format!("a=mediaclk:{clock}"),
             SdpProp::Attribute { key, value } => {
                 if let Some(value) = value {
                     format!("a={}:{}", key, value)
@@ -578,6 +736,316 @@ impl SdpProp {
     }
 }
 
+/// Format parameters of an `a=fmtp:<pt> <params>` attribute
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Fmtp {
+    pub payload_type: u8,
+    /// `key=value` pairs separated by `;`, value-less parameters are kept as `None`
+    pub parameters: Vec<(String, Option<String>)>,
+}
+
+impl Fmtp {
+    pub fn parameter(&self, key: &str) -> Option<&str> {
+        self.parameters
+            .iter()
+            .find(|(k, _)| k == key)
+            .and_then(|(_, v)| v.as_deref())
+    }
+}
+
+impl FromStr for Fmtp {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (payload_type, params) = s.split_once(' ').unwrap_or((s, ""));
+
+        Ok(Fmtp {
+            payload_type: payload_type.parse()?,
+            parameters: params
+                .split(';')
+                .map(|param| param.trim())
+                .filter(|param| !param.is_empty())
+                .map(|param| match param.split_once('=') {
+                    Some((key, value)) => (key.to_string(), Some(value.to_string())),
+                    None => (param.to_string(), None),
+                })
+                .collect(),
+        })
+    }
+}
+
+impl ToString for Fmtp {
+    fn to_string(&self) -> String {
+        format!(
+            "{} {}",
+            self.payload_type,
+            self.parameters
+                .iter()
+                .map(|(key, value)| match value {
+                    Some(value) => format!("{key}={value}"),
+                    None => key.clone(),
+                })
+                .collect::<Vec<String>>()
+                .join(";")
+        )
+    }
+}
+
+/// Value of an `a=setup` attribute (RFC 4145)
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SetupRole {
+    /// Initiates the connection, the DTLS client
+    Active,
+    /// Accepts the connection, the DTLS server
+    Passive,
+    /// Either, offered to let the answerer choose
+    ActPass,
+    HoldConn,
+}
+
+impl FromStr for SetupRole {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "active" => Ok(SetupRole::Active),
+            "passive" => Ok(SetupRole::Passive),
+            "actpass" => Ok(SetupRole::ActPass),
+            "holdconn" => Ok(SetupRole::HoldConn),
+            _ => Err(ParseError::UnknownToken(s.to_string())),
+        }
+    }
+}
+
+impl Display for SetupRole {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SetupRole::Active => "active",
+            SetupRole::Passive => "passive",
+            SetupRole::ActPass => "actpass",
+            SetupRole::HoldConn => "holdconn",
+        })
+    }
+}
+
+/// `tcptype` of a TCP candidate (RFC 6544)
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TcpType {
+    Active,
+    Passive,
+    SimultaneousOpen,
+}
+
+impl FromStr for TcpType {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "active" => Ok(TcpType::Active),
+            "passive" => Ok(TcpType::Passive),
+            "so" => Ok(TcpType::SimultaneousOpen),
+            _ => Err(ParseError::UnknownToken(s.to_string())),
+        }
+    }
+}
+
+impl Display for TcpType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            TcpType::Active => "active",
+            TcpType::Passive => "passive",
+            TcpType::SimultaneousOpen => "so",
+        })
+    }
+}
+
+/// ICE candidate of an `a=candidate:<candidate>` attribute (RFC 8839)
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Candidate {
+    pub foundation: String,
+    pub component: u16,
+    /// `udp` or `tcp`, kept in the case it was received in
+    pub transport: String,
+    pub priority: u32,
+    pub address: String,
+    pub port: u16,
+    /// `host`, `srflx`, `prflx` or `relay`
+    pub typ: String,
+    pub related_address: Option<String>,
+    pub related_port: Option<u16>,
+    /// Only present on TCP candidates
+    pub tcp_type: Option<TcpType>,
+    /// Remaining `name value` pairs in order, e.g. `generation 0`
+    pub extensions: Vec<(String, String)>,
+}
+
+impl Candidate {
+    pub fn is_tcp(&self) -> bool {
+        self.transport.eq_ignore_ascii_case("tcp")
+    }
+}
+
+impl FromStr for Candidate {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = s.split(' ').filter(|token| !token.is_empty()).collect::<Vec<&str>>();
+        if tokens.len() < 8 || tokens[6] != "typ" {
+            return Err(ParseError::UnknownToken(s.to_string()));
+        }
+
+        let mut candidate = Candidate {
+            foundation: tokens[0].to_string(),
+            component: tokens[1].parse()?,
+            transport: tokens[2].to_string(),
+            priority: tokens[3].parse()?,
+            address: tokens[4].to_string(),
+            port: tokens[5].parse()?,
+            typ: tokens[7].to_string(),
+            related_address: None,
+            related_port: None,
+            tcp_type: None,
+            extensions: vec![],
+        };
+
+        for pair in tokens[8..].chunks(2) {
+            let (name, value) = match pair {
+                [name, value] => (*name, *value),
+                _ => return Err(ParseError::UnknownToken(s.to_string())),
+            };
+
+            match name {
+                "raddr" => candidate.related_address = Some(value.to_string()),
+                "rport" => candidate.related_port = Some(value.parse()?),
+                "tcptype" => candidate.tcp_type = Some(TcpType::from_str(value)?),
+                _ => candidate.extensions.push((name.to_string(), value.to_string())),
+            }
+        }
+
+        Ok(candidate)
+    }
+}
+
+impl Display for Candidate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {} {} typ {}",
+            self.foundation, self.component, self.transport, self.priority, self.address, self.port, self.typ
+        )?;
+
+        if let Some(address) = &self.related_address {
+            write!(f, " raddr {address}")?;
+        }
+        if let Some(port) = self.related_port {
+            write!(f, " rport {port}")?;
+        }
+        if let Some(tcp_type) = self.tcp_type {
+            write!(f, " tcptype {tcp_type}")?;
+        }
+        for (name, value) in &self.extensions {
+            write!(f, " {name} {value}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Value of an `a=ts-refclk` attribute, the clock RTP timestamps are derived from (RFC 7273)
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum RefClock {
+    /// `ntp=<server>`, or `ntp=/traceable/`
+    Ntp(String),
+    /// `ptp=<version>:<grandmaster>[:<domain>]` as written, e.g. `IEEE1588-2008:39-A7-94-FF-FE-07-CB-D0:0`
+    Ptp(String),
+    Gps,
+    Galileo,
+    Glonass,
+    /// The sender's local clock, not synchronized to anything
+    Local,
+    /// `localmac=<address>`, a local clock identified by the MAC address of the sender
+    LocalMac(String),
+    /// Sources this parser doesn't model, such as `private`, kept as written
+    Other(String),
+}
+
+/// Never fails, unknown sources are kept as `RefClock::Other`
+impl FromStr for RefClock {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.split_once('=') {
+            Some(("ntp", server)) => RefClock::Ntp(server.to_string()),
+            Some(("ptp", clock)) => RefClock::Ptp(clock.to_string()),
+            Some(("localmac", address)) => RefClock::LocalMac(address.to_string()),
+            _ => match s {
+                "gps" => RefClock::Gps,
+                "gal" => RefClock::Galileo,
+                "glonass" => RefClock::Glonass,
+                "local" => RefClock::Local,
+                _ => RefClock::Other(s.to_string()),
+            },
+        })
+    }
+}
+
+impl Display for RefClock {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RefClock::Ntp(server) => write!(f, "ntp={server}"),
+            RefClock::Ptp(clock) => write!(f, "ptp={clock}"),
+            RefClock::Gps => f.write_str("gps"),
+            RefClock::Galileo => f.write_str("gal"),
+            RefClock::Glonass => f.write_str("glonass"),
+            RefClock::Local => f.write_str("local"),
+            RefClock::LocalMac(address) => write!(f, "localmac={address}"),
+            RefClock::Other(source) => f.write_str(source),
+        }
+    }
+}
+
+/// Value of an `a=mediaclk` attribute, how the media clock relates to the reference clock (RFC 7273)
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum MediaClock {
+    /// `direct=<offset>[ rate=<rate>]`, RTP timestamps count from `offset` at the reference clock's epoch
+    Direct { offset: u64, rate: Option<String> },
+    /// The clock is signaled in-band with RTCP sender reports
+    Sender,
+    /// Clocks this parser doesn't model, such as `IEEE1722=...`, kept as written
+    Other(String),
+}
+
+/// Never fails, unknown clocks are kept as `MediaClock::Other`
+impl FromStr for MediaClock {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "sender" {
+            return Ok(MediaClock::Sender);
+        }
+
+        let direct = s.strip_prefix("direct=").and_then(|direct| {
+            let (offset, rate) = match direct.split_once(' ') {
+                Some((offset, rate)) => (offset, Some(rate.strip_prefix("rate=")?.to_string())),
+                None => (direct, None),
+            };
+            Some(MediaClock::Direct { offset: offset.parse().ok()?, rate })
+        });
+        Ok(direct.unwrap_or_else(|| MediaClock::Other(s.to_string())))
+    }
+}
+
+impl Display for MediaClock {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MediaClock::Direct { offset, rate: Some(rate) } => write!(f, "direct={offset} rate={rate}"),
+            MediaClock::Direct { offset, rate: None } => write!(f, "direct={offset}"),
+            MediaClock::Sender => f.write_str("sender"),
+            MediaClock::Other(clock) => f.write_str(clock),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum ParseError {
     /// Unknown attribute key along with its value
@@ -585,6 +1053,8 @@ pub enum ParseError {
     UnknownToken(String),
     /// Failed to cast from String to another type
     TypeParseFailed(IntErrorKind),
+    /// The JSON isn't a session description of the form `{"type":"offer","sdp":"..."}`
+    InvalidJsep(String),
 }
 
 impl From<ParseIntError> for ParseError {
@@ -598,6 +1068,16 @@ pub struct SDP {
     pub props: Vec<SdpProp>,
 }
 
+/// Lines that are only valid before the first media section (RFC 4566 section 5)
+const SESSION_ONLY_KEYS: [char; 9] = ['v', 'o', 's', 'u', 'e', 'p', 't', 'r', 'z'];
+
+/// Parses a description, grouping every line after an `m=` line into that media section
+///
+/// Noncompliant descriptions sometimes place session-only lines (`v=`, `o=`, `s=`, `u=`, `e=`, `p=`, `t=`,
+/// `r=`, `z=`) after the first media section. These are hoisted to the end of the session level, in order.
+/// Lines that are also valid in a media section (`i=`, `c=`, `b=`, `k=`, `a=`) stay with the preceding section.
+/// Trailing whitespace is dropped, webrtc-rs ends `a=rtcp-fb` lines without a parameter with a space that it
+/// doesn't write anymore once the description has been set.
 impl FromStr for SDP {
     type Err = ParseError;
 
@@ -605,50 +1085,40 @@ impl FromStr for SDP {
         // Convert \r\n to \n
         let s = s.replace("\r\n", "\n");
 
-        // Split string
-        let lines = s
-            .split('\n')
-            .map(|line| line.to_string())
-            .collect::<Vec<String>>();
-
         // Group media attributes
-        // Find indexes of all media lines
-        let m_indices = lines
-            .iter()
-            .enumerate()
-            .filter(|(_, line)| line.starts_with('m'))
-            .map(|(idx, _)| idx)
-            .collect::<Vec<_>>();
-
-        // Combine all media sections into one line per section
-        let lines: Vec<String> =
-            lines
-                .into_iter()
-                .filter(|line| !line.is_empty())
-                .enumerate()
-                .fold(Vec::new(), |mut acc, (idx, line)| {
-                    // If m-line detected or array empty, start a new section
-                    if acc.is_empty()
-                        || m_indices.contains(&idx)
-                        || m_indices.is_empty()
-                        || idx < m_indices[0]
-                    {
-                        acc.push(line);
-                        return acc;
-                    }
-
-                    // Add to current section
-                    *acc.last_mut().unwrap() = format!("{}\n{line}", acc.last_mut().unwrap());
+        // Session lines are one section each, every media section collects its lines and is joined once
+        let mut sections: Vec<Vec<&str>> = Vec::new();
+        let mut hoisted = Vec::new();
+        let mut in_media = false;
+        for line in s.split('\n').map(str::trim_end).filter(|line| !line.is_empty()) {
+            if line.starts_with('m') {
+                in_media = true;
+                sections.push(vec![line]);
+            } else if in_media && line.starts_with(SESSION_ONLY_KEYS) {
+                hoisted.push(line);
+            } else if in_media {
+                sections.last_mut().unwrap().push(line);
+            } else {
+                sections.push(vec![line]);
+            }
+        }
+        let lines = sections.into_iter().map(|section| section.join("\n"));
 
-                    acc
-                });
+        let mut props = lines
+            .map(|line| SdpProp::from_str(&line))
+            .collect::<Result<Vec<_>, _>>()?;
 
-        Ok(Self {
-            props: lines
-                .into_iter()
-                .map(|line| SdpProp::from_str(&line))
-                .collect::<Result<Vec<_>, _>>()?,
-        })
+        let first_media = props
+            .iter()
+            .position(|prop| matches!(prop, SdpProp::Media { .. }))
+            .unwrap_or(props.len());
+        let hoisted = hoisted
+            .into_iter()
+            .map(SdpProp::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        props.splice(first_media..first_media, hoisted);
+
+        Ok(Self { props })
     }
 }
 
@@ -660,6 +1130,446 @@ impl SDP {
             .collect::<Vec<String>>()
             .join(ending.string()), ending.string())
     }
+
+    /// Builds a minimal offer with one bundled media section per `(kind, codec)`, e.g. `(MediaType::Audio, "opus/48000/2")`
+    ///
+    /// Codecs are given as `a=rtpmap` encodings and get dynamic payload types from 96 to 127, then from 35 to 63, `None`
+    /// if there are more RTP sections than that. The codec of an `Application` section is ignored and a data channel
+    /// section is created instead. The offer carries no ICE or DTLS parameters, so it is meant for tests
+    /// and examples rather than a real peer connection.
+    pub fn minimal_offer(media: &[(MediaType, &str)]) -> Option<SDP> {
+        let mids = (0..media.len()).map(|mid| mid.to_string()).collect::<Vec<_>>();
+        let mut props = vec![
+            SdpProp::Version(0),
+            SdpProp::Origin {
+                username: "-".to_string(),
+                session_id: "0".to_string(),
+                session_version: 0,
+                net_type: NetworkType::Internet,
+                address_type: AddressType::IPv4,
+                address: "0.0.0.0".to_string(),
+            },
+            SdpProp::SessionName("-".to_string()),
+            SdpProp::Timing { start: 0, stop: 0 },
+        ];
+        let mut payload_types = (96..=127).chain(35..=63);
+        if !media.is_empty() {
+            props.push(SdpProp::Attribute {
+                key: "group".to_string(),
+                value: Some(format!("BUNDLE {}", mids.join(" "))),
+            });
+        }
+
+        for (idx, (kind, codec)) in media.iter().enumerate() {
+            let attribute = |key: &str, value: Option<String>| MediaProp::Attribute { key: key.to_string(), value };
+            let mut media_props = vec![
+                MediaProp::Connection {
+                    net_type: NetworkType::Internet,
+                    address_type: AddressType::IPv4,
+                    address: "0.0.0.0".to_string(),
+                    ttl: None,
+                    num_addresses: None,
+                    suffix: None,
+                },
+                attribute("mid", Some(mids[idx].clone())),
+            ];
+
+            let (protocol, format) = if *kind == MediaType::Application {
+                media_props.push(MediaProp::SctpPort(5000));
+                ("UDP/DTLS/SCTP", "webrtc-datachannel".to_string())
+            } else {
+                let payload_type: u8 = payload_types.next()?;
+                media_props.push(attribute("sendrecv", None));
+                media_props.push(attribute("rtcp-mux", None));
+                media_props.push(attribute("rtpmap", Some(format!("{payload_type} {codec}"))));
+                ("UDP/TLS/RTP/SAVPF", payload_type.to_string())
+            };
+
+            props.push(SdpProp::Media {
+                r#type: *kind,
+                ports: vec![9],
+                protocol: protocol.to_string(),
+                format,
+                props: media_props,
+            });
+        }
+
+        Some(SDP { props })
+    }
+
+    /// Returns `(rtx_pt, primary_pt)` pairs from the `apt` fmtp parameters of the media section at `media_index`
+    pub fn rtx_associations(&self, media_index: usize) -> Vec<(u8, u8)> {
+        let props = match self.media_props(media_index) {
+            Some(props) => props,
+            None => return vec![],
+        };
+
+        props
+            .iter()
+            .filter_map(|prop| match prop {
+                MediaProp::Attribute { key, value: Some(value) } if key == "fmtp" => Fmtp::from_str(value).ok(),
+                _ => None,
+            })
+            .filter_map(|fmtp| {
+                let apt = fmtp.parameter("apt")?.parse().ok()?;
+                Some((fmtp.payload_type, apt))
+            })
+            .collect()
+    }
+
+    /// Reorders the payload types of each media section to follow the codec order of the matching section in `remote`
+    ///
+    /// Sections are matched by index, as required for an answer to an offer. Codecs the remote doesn't list keep their
+    /// relative order after the mutually supported ones.
+    pub fn mirror_codec_order(&mut self, remote: &SDP) {
+        let remote_sections = remote
+            .props
+            .iter()
+            .filter_map(|prop| match prop {
+                SdpProp::Media { format, props, .. } => Some((format, rtpmaps(props))),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let local_sections = self.props.iter_mut().filter_map(|prop| match prop {
+            SdpProp::Media { format, props, .. } => Some((format, rtpmaps(props))),
+            _ => None,
+        });
+
+        for ((format, codecs), (remote_format, remote_codecs)) in local_sections.zip(remote_sections) {
+            let remote_order = remote_format.split(' ').collect::<Vec<_>>();
+            let position = |pt: &str| {
+                let codec = codecs.iter().find(|(p, _)| p == pt).map(|(_, codec)| codec);
+                let matches = |remote_pt: &&str| {
+                    remote_codecs
+                        .iter()
+                        .find(|(p, _)| p == remote_pt)
+                        .map(|(_, remote_codec)| Some(remote_codec) == codec)
+                        .unwrap_or(false)
+                };
+
+                // Prefer the exact payload type, then any payload type with the same codec
+                remote_order
+                    .iter()
+                    .position(|remote_pt| *remote_pt == pt && matches(remote_pt))
+                    .or_else(|| remote_order.iter().position(matches))
+                    .unwrap_or(usize::MAX)
+            };
+
+            let mut pts = format.split(' ').map(|pt| pt.to_string()).collect::<Vec<_>>();
+            pts.sort_by_key(|pt| position(pt));
+            *format = pts.join(" ");
+        }
+    }
+
+    /// Returns the `(ufrag, pwd)` of the first media section with both, falling back to session level ones
+    pub fn ice_credentials(&self) -> Option<(&str, &str)> {
+        let media = self.props.iter().find_map(|prop| match prop {
+            SdpProp::Media { props, .. } => {
+                let ufrag = props.iter().find_map(|prop| match prop {
+                    MediaProp::IceUfrag(ufrag) => Some(ufrag.as_str()),
+                    _ => None,
+                })?;
+                let pwd = props.iter().find_map(|prop| match prop {
+                    MediaProp::IcePwd(pwd) => Some(pwd.as_str()),
+                    _ => None,
+                })?;
+                Some((ufrag, pwd))
+            }
+            _ => None,
+        });
+
+        media.or_else(|| {
+            let ufrag = self.props.iter().find_map(|prop| match prop {
+                SdpProp::IceUfrag(ufrag) => Some(ufrag.as_str()),
+                _ => None,
+            })?;
+            let pwd = self.props.iter().find_map(|prop| match prop {
+                SdpProp::IcePwd(pwd) => Some(pwd.as_str()),
+                _ => None,
+            })?;
+            Some((ufrag, pwd))
+        })
+    }
+
+    /// Returns the `a=mid` of every media section in order, `None` for sections without one
+    pub fn media_mids(&self) -> Vec<Option<&str>> {
+        self.props
+            .iter()
+            .filter_map(|prop| match prop {
+                SdpProp::Media { props, .. } => Some(mid(props)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the ids of each `a=group:BUNDLE` line
+    pub fn bundle_groups(&self) -> Vec<Vec<&str>> {
+        self.props
+            .iter()
+            .filter_map(|prop| match prop {
+                SdpProp::Attribute { key, value: Some(value) } if key == "group" => {
+                    let mut tokens = value.split(' ');
+                    (tokens.next() == Some("BUNDLE")).then(|| tokens.filter(|id| !id.is_empty()).collect())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Whether every BUNDLE group only lists mids of media sections that are in use, in media section order
+    pub fn is_bundle_consistent(&self) -> bool {
+        let order = self.bundlable_mids();
+        self.bundle_groups().iter().all(|group| {
+            let positions = group
+                .iter()
+                .map(|id| order.iter().position(|mid| mid == id))
+                .collect::<Option<Vec<_>>>();
+            positions.is_some_and(|positions| positions.windows(2).all(|pair| pair[0] < pair[1]))
+        })
+    }
+
+    /// Rewrites every BUNDLE group to follow the media section order, dropping ids that no longer match a section
+    /// in use. Groups left without ids are kept as a bare `a=group:BUNDLE`.
+    pub fn normalize_bundle(&mut self) {
+        let order = self.bundlable_mids().into_iter().map(str::to_string).collect::<Vec<_>>();
+        for prop in self.props.iter_mut() {
+            if let SdpProp::Attribute { key, value: Some(value) } = prop {
+                let mut tokens = value.split(' ');
+                if key != "group" || tokens.next() != Some("BUNDLE") {
+                    continue;
+                }
+
+                let group = tokens.collect::<Vec<_>>();
+                let ids = order
+                    .iter()
+                    .filter(|mid| group.contains(&mid.as_str()))
+                    .map(String::as_str)
+                    .collect::<Vec<_>>();
+                *value = format!("BUNDLE {}", ids.join(" "));
+            }
+        }
+    }
+
+    /// Mids of the media sections that aren't rejected, in order
+    fn bundlable_mids(&self) -> Vec<&str> {
+        self.props
+            .iter()
+            .filter(|prop| !prop.is_rejected())
+            .filter_map(|prop| match prop {
+                SdpProp::Media { props, .. } => mid(props),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the `a=setup` of the first media section with one, bundled sections share it
+    pub fn setup_role(&self) -> Option<SetupRole> {
+        self.props.iter().find_map(|prop| match prop {
+            SdpProp::Media { props, .. } => props.iter().find_map(|prop| match prop {
+                MediaProp::Setup(role) => Some(*role),
+                _ => None,
+            }),
+            _ => None,
+        })
+    }
+
+    /// Returns the `a=content` of the media section at `media_index`
+    pub fn content(&self, media_index: usize) -> Option<&str> {
+        self.media_props(media_index)?.iter().find_map(|prop| match prop {
+            MediaProp::Content(content) => Some(content.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Serializes only the media section at `media_index`, from its `m=` line to the last of its attributes
+    pub fn media_to_string(&self, media_index: usize, ending: LineEnding) -> Option<String> {
+        self.props
+            .iter()
+            .filter(|prop| matches!(prop, SdpProp::Media { .. }))
+            .nth(media_index)
+            .map(|media| format!("{}{}", media.to_string(ending), ending.string()))
+    }
+
+    /// Serializes to the JSON form of an `RTCSessionDescription`, `{"type":"offer","sdp":"..."}`, as exchanged by
+    /// browser signaling
+    pub fn to_jsep(&self, sdp_type: RTCSdpType) -> String {
+        let mut description = RTCSessionDescription::default();
+        description.sdp = self.to_string(LineEnding::CRLF);
+        description.sdp_type = sdp_type;
+
+        serde_json::to_string(&description).expect("Failed to serialize session description")
+    }
+
+    /// Parses the JSON form of an `RTCSessionDescription` into the description and its type
+    pub fn from_jsep(json: &str) -> Result<(SDP, RTCSdpType), ParseError> {
+        let description = serde_json::from_str::<RTCSessionDescription>(json).map_err(|e| ParseError::InvalidJsep(e.to_string()))?;
+
+        Ok((SDP::from_str(&description.sdp)?, description.sdp_type))
+    }
+
+    /// Returns the `SdpProp::Media` of every media section in order
+    pub fn media_sections(&self) -> Vec<&SdpProp> {
+        self.props
+            .iter()
+            .filter(|prop| matches!(prop, SdpProp::Media { .. }))
+            .collect()
+    }
+
+    /// Returns the value of the first session level `a=<key>` line, empty for flags like `a=ice-lite`
+    pub fn get_attribute(&self, key: &str) -> Option<String> {
+        self.props
+            .iter()
+            .take_while(|prop| !matches!(prop, SdpProp::Media { .. }))
+            .find_map(|prop| attribute_value(&prop.to_string(LineEnding::LF), key))
+    }
+
+    /// Returns the value of the first `a=<key>` line of the media section at `media_index`, empty for flags
+    pub fn media_attribute(&self, media_index: usize, key: &str) -> Option<String> {
+        self.media_props(media_index)?
+            .iter()
+            .find_map(|prop| attribute_value(&prop.to_string(), key))
+    }
+
+    /// Replaces the `b=` lines of the media section at `media_index` with `b=AS:<kbps>`, returns `false` if there
+    /// is no such section
+    pub fn set_bandwidth(&mut self, media_index: usize, kbps: usize) -> bool {
+        let props = match self.media_props_mut(media_index) {
+            Some(props) => props,
+            None => return false,
+        };
+
+        props.retain(|prop| !matches!(prop, MediaProp::Bandwidth { .. }));
+        // b= follows the i= and c= lines (RFC 4566 section 5)
+        let position = props
+            .iter()
+            .position(|prop| !matches!(prop, MediaProp::Title(_) | MediaProp::Connection { .. }))
+            .unwrap_or(props.len());
+        props.insert(position, MediaProp::Bandwidth {
+            r#type: BandwidthType::ApplicationSpecific,
+            bandwidth: kbps,
+        });
+
+        true
+    }
+
+    /// Appends `a=<key>[:<value>]` to the media section at `media_index`, returns `false` if there is no such section
+    ///
+    /// Attributes with a typed `MediaProp` are added as that variant, so they read back like parsed ones.
+    pub fn add_attribute(&mut self, media_index: usize, key: &str, value: Option<&str>) -> bool {
+        let props = match self.media_props_mut(media_index) {
+            Some(props) => props,
+            None => return false,
+        };
+
+        let line = match value {
+            Some(value) => format!("a={key}:{value}"),
+            None => format!("a={key}"),
+        };
+        let prop = MediaProp::from_str(&line).unwrap_or_else(|_| MediaProp::Attribute {
+            key: key.to_string(),
+            value: value.map(str::to_string),
+        });
+        props.push(prop);
+
+        true
+    }
+
+    /// Removes every `a=<key>` line of the media section at `media_index`, returns whether any was removed
+    pub fn remove_attribute(&mut self, media_index: usize, key: &str) -> bool {
+        let props = match self.media_props_mut(media_index) {
+            Some(props) => props,
+            None => return false,
+        };
+
+        let len = props.len();
+        props.retain(|prop| attribute_value(&prop.to_string(), key).is_none());
+        props.len() != len
+    }
+
+    /// Removes `payload_type` from the media section at `media_index` along with its `a=rtpmap`, `a=fmtp` and
+    /// `a=rtcp-fb` lines, returns whether the section listed it
+    ///
+    /// RTX payload types associated with it through `apt` are left in place, remove them separately with
+    /// the pairs from `rtx_associations`.
+    pub fn remove_payload_type(&mut self, media_index: usize, payload_type: u8) -> bool {
+        let section = self
+            .props
+            .iter_mut()
+            .filter(|prop| matches!(prop, SdpProp::Media { .. }))
+            .nth(media_index);
+        let (format, props) = match section {
+            Some(SdpProp::Media { format, props, .. }) => (format, props),
+            _ => return false,
+        };
+
+        let pt = payload_type.to_string();
+        let pts = format.split(' ').filter(|p| *p != pt).collect::<Vec<_>>().join(" ");
+        if pts == *format {
+            return false;
+        }
+        *format = pts;
+
+        props.retain(|prop| match prop {
+            MediaProp::Attribute { key, value: Some(value) } if ["rtpmap", "fmtp", "rtcp-fb"].contains(&key.as_str()) => {
+                value.split(' ').next() != Some(pt.as_str())
+            }
+            _ => true,
+        });
+
+        true
+    }
+
+    fn media_props(&self, media_index: usize) -> Option<&Vec<MediaProp>> {
+        self.props
+            .iter()
+            .filter_map(|prop| match prop {
+                SdpProp::Media { props, .. } => Some(props),
+                _ => None,
+            })
+            .nth(media_index)
+    }
+
+    fn media_props_mut(&mut self, media_index: usize) -> Option<&mut Vec<MediaProp>> {
+        self.props
+            .iter_mut()
+            .filter_map(|prop| match prop {
+                SdpProp::Media { props, .. } => Some(props),
+                _ => None,
+            })
+            .nth(media_index)
+    }
+}
+
+/// Returns the value of `line` if it is an `a=<key>` attribute, empty for flags
+fn attribute_value(line: &str, key: &str) -> Option<String> {
+    let rest = line.strip_prefix("a=")?.strip_prefix(key)?;
+    if rest.is_empty() {
+        Some(String::new())
+    } else {
+        rest.strip_prefix(':').map(str::to_string)
+    }
+}
+
+/// Returns the `a=mid` of a media section
+fn mid(props: &[MediaProp]) -> Option<&str> {
+    props.iter().find_map(|prop| match prop {
+        MediaProp::Attribute { key, value: Some(mid) } if key == "mid" => Some(mid.as_str()),
+        _ => None,
+    })
+}
+
+/// Maps payload types to their lowercased `a=rtpmap` encoding
+fn rtpmaps(props: &[MediaProp]) -> Vec<(String, String)> {
+    props
+        .iter()
+        .filter_map(|prop| match prop {
+            MediaProp::Attribute { key, value: Some(value) } if key == "rtpmap" => value
+                .split_once(' ')
+                .map(|(pt, codec)| (pt.to_string(), codec.to_lowercase())),
+            _ => None,
+        })
+        .collect()
 }
 
 fn content_from_line(line: &str) -> Result<(char, String), ParseError> {
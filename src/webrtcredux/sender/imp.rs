@@ -1,20 +1,92 @@
-use std::sync::{Mutex, Arc};
-use std::time::Duration;
+use std::sync::{Condvar, Mutex, Arc};
+use std::time::{Duration, Instant};
 
 use bytes::Bytes;
 use futures::executor::block_on;
 use gst::prelude::ClockExtManual;
-use gst::traits::{ClockExt, ElementExt};
-use gst::{Buffer, FlowError, FlowSuccess, glib, trace, ClockTime, debug, error};
+use gst::traits::{ClockExt, ElementExt, PadExt};
+use gst::{Buffer, BufferRef, FlowError, FlowSuccess, glib, trace, ClockTime, debug, error, warning};
 use gst::subclass::ElementMetadata;
 use gst::subclass::prelude::*;
+use gst_base::prelude::BaseSinkExtManual;
 use gst_base::subclass::prelude::*;
 use once_cell::sync::Lazy;
+use strum_macros::{Display, EnumString};
 use tokio::runtime::Handle;
 use webrtc::media::Sample;
+use webrtc::rtp::extension::HeaderExtension;
+use webrtc::rtp::packet::Packet;
+use webrtc::util::{Marshal, MarshalSize, Unmarshal};
+use webrtc::track::track_local::TrackLocal;
+use webrtc::track::track_local::track_local_static_rtp::TrackLocalStaticRTP;
 use webrtc::track::track_local::track_local_static_sample::TrackLocalStaticSample;
 
-use crate::webrtcredux::CAT;
+use crate::webrtcredux::{clock_rate, CAT, H264_PROFILES, OPUS_SAMPLE_RATES, RTP_AUDIO_ENCODINGS, RTP_VIDEO_ENCODINGS};
+
+/// Produces the RTP header extensions for the next sample
+pub type HeaderExtensionsFn = Arc<dyn Fn() -> Vec<HeaderExtension> + Send + Sync>;
+
+/// Name of the `gst::ProtectionMeta` structure carrying a header extension value for a buffer
+pub const HEADER_EXTENSION_META: &str = "webrtcredux/header-extension";
+
+/// Largest value of a one-byte header extension (RFC 8285), values must be 1 to 16 bytes long
+pub const MAX_ONE_BYTE_EXTENSION_SIZE: usize = 16;
+
+/// Attaches `data` to `buffer` as the value of the header extension negotiated for `uri`
+///
+/// The extension has to be registered with `WebRtcRedux::register_header_extension` before starting,
+/// values longer than [`MAX_ONE_BYTE_EXTENSION_SIZE`] are dropped.
+pub fn add_header_extension_meta(buffer: &mut BufferRef, uri: &str, data: &[u8]) {
+    gst::ProtectionMeta::add(
+        buffer,
+        gst::Structure::builder(HEADER_EXTENSION_META)
+            .field("uri", uri)
+            .field("data", glib::Bytes::from(data))
+            .build(),
+    );
+}
+
+/// Header extension value written as is
+struct RawExtension(Bytes);
+
+impl MarshalSize for RawExtension {
+    fn marshal_size(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl Marshal for RawExtension {
+    fn marshal_to(&self, buf: &mut [u8]) -> Result<usize, webrtc::util::Error> {
+        if buf.len() < self.0.len() {
+            return Err(webrtc::util::Error::ErrBufferShort);
+        }
+
+        buf[..self.0.len()].copy_from_slice(&self.0);
+        Ok(self.0.len())
+    }
+}
+
+/// Collects the header extension values attached to `buffer` with [`add_header_extension_meta`]
+fn meta_header_extensions(buffer: &BufferRef) -> Vec<HeaderExtension> {
+    buffer
+        .iter_meta::<gst::ProtectionMeta>()
+        .filter(|meta| meta.info().name() == HEADER_EXTENSION_META)
+        .filter_map(|meta| {
+            let info = meta.info();
+            let uri = info.get::<String>("uri").ok()?;
+            let data = info.get::<glib::Bytes>("data").ok()?;
+            if data.is_empty() || data.len() > MAX_ONE_BYTE_EXTENSION_SIZE {
+                warning!(CAT, "Dropping {} byte value for header extension {}", data.len(), uri);
+                return None;
+            }
+
+            Some(HeaderExtension::Custom {
+                uri: uri.into(),
+                extension: Box::new(RawExtension(Bytes::copy_from_slice(&data))),
+            })
+        })
+        .collect()
+}
 
 #[derive(PartialEq, Eq)]
 pub enum MediaType {
@@ -22,22 +94,85 @@ pub enum MediaType {
     Audio
 }
 
+/// How a pad's buffers are sent, chosen per pad before its track is created
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, EnumString, Display)]
+#[strum(ascii_case_insensitive, serialize_all = "lowercase")]
+pub enum TrackMode {
+    /// Encoded frames, packetized by webrtc-rs
+    #[default]
+    Sample,
+    /// `application/x-rtp` packets from an upstream payloader, forwarded as is
+    Rtp,
+}
+
+/// Frames the sender discarded instead of writing them to its track, while muted or because a flush interrupted them
+/// waiting for their pacing slot. In RTP mode every discarded packet counts as a frame.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DroppedFrames {
+    /// Video keyframes and all audio frames
+    pub keyframes: u64,
+    /// Video frames flagged `DELTA_UNIT`
+    pub delta_frames: u64,
+}
+
+pub type DroppedFramesFn = Arc<dyn Fn(DroppedFrames) + Send + Sync>;
+
+/// Local track written by the sender
+#[derive(Clone)]
+pub enum Track {
+    Sample(Arc<TrackLocalStaticSample>),
+    Rtp(Arc<TrackLocalStaticRTP>),
+}
+
+impl Track {
+    pub fn track_local(&self) -> Arc<dyn TrackLocal + Send + Sync> {
+        match self {
+            Track::Sample(track) => track.clone(),
+            Track::Rtp(track) => track.clone(),
+        }
+    }
+}
+
 #[derive(Default)]
 struct State {
-    track: Option<Arc<TrackLocalStaticSample>>,
+    track: Option<Track>,
+    mode: TrackMode,
+    /// Negotiate the caps of both modes, the element picks the mode from the caps it receives
+    auto_mode: bool,
     duration: Option<ClockTime>,
     handle: Option<Handle>,
     media_type: Option<MediaType>,
-    async_complete: bool
+    async_complete: bool,
+    /// RTP header extensions written on every packet
+    extensions: Option<HeaderExtensionsFn>,
+    /// Called once the first video keyframe has been written to the track
+    on_first_keyframe: Option<Box<dyn FnOnce() + Send>>,
+    /// Write samples at most once per sample duration instead of as soon as they are rendered
+    frame_pacing: bool,
+    /// Earliest time the next paced sample may be written
+    next_write: Option<Instant>,
+    /// Set between `unlock` and `unlock_stop` while a flush is in progress
+    flushing: bool,
+    dropped: DroppedFrames,
+    /// Called with the new totals whenever a frame is dropped
+    on_dropped: Option<DroppedFramesFn>,
+    /// Discard buffers instead of writing them, the track stays negotiated
+    muted: bool,
+    /// Running time of the first timestamped sample and the RTP ticks written since
+    timing: Option<(ClockTime, u64)>,
+    /// Duration of the latest buffer that had one, used for buffers without
+    last_duration: Option<ClockTime>,
 }
 
 #[derive(Default)]
 pub struct WebRtcReduxSender {
     state: Arc<Mutex<State>>,
+    /// Wakes a paced render up when flushing starts
+    unlock: Condvar,
 }
 
 impl WebRtcReduxSender {
-    pub fn add_info(&self, track: Arc<TrackLocalStaticSample>, handle: Handle, media_type: MediaType, duration: Option<ClockTime>, on_connect: tokio::sync::oneshot::Receiver<()>) {
+    pub fn add_info(&self, track: Track, handle: Handle, media_type: MediaType, duration: Option<ClockTime>, on_connect: tokio::sync::oneshot::Receiver<()>) {
         let _ = self.state.lock().unwrap().track.insert(track);
         let _ = self.state.lock().unwrap().media_type.insert(media_type);
         self.state.lock().unwrap().duration = duration;
@@ -52,6 +187,46 @@ impl WebRtcReduxSender {
         });
         let _ = self.state.lock().unwrap().handle.insert(handle);
     }
+
+    /// Replaces the track written to, e.g. after negotiation picked another codec
+    pub fn set_track(&self, track: Track) {
+        let _ = self.state.lock().unwrap().track.insert(track);
+    }
+
+    pub fn set_track_mode(&self, mode: TrackMode) {
+        let mut state = self.state.lock().unwrap();
+        state.mode = mode;
+        state.auto_mode = false;
+    }
+
+    /// Accepts the caps of both modes until `set_track_mode` settles one
+    pub fn set_auto_track_mode(&self) {
+        self.state.lock().unwrap().auto_mode = true;
+    }
+
+    pub fn set_header_extensions(&self, extensions: HeaderExtensionsFn) {
+        let _ = self.state.lock().unwrap().extensions.insert(extensions);
+    }
+
+    pub fn set_on_first_keyframe(&self, f: Box<dyn FnOnce() + Send>) {
+        let _ = self.state.lock().unwrap().on_first_keyframe.insert(f);
+    }
+
+    pub fn set_frame_pacing(&self, frame_pacing: bool) {
+        self.state.lock().unwrap().frame_pacing = frame_pacing;
+    }
+
+    pub fn set_muted(&self, muted: bool) {
+        self.state.lock().unwrap().muted = muted;
+    }
+
+    pub fn dropped_frames(&self) -> DroppedFrames {
+        self.state.lock().unwrap().dropped
+    }
+
+    pub fn set_on_dropped(&self, f: DroppedFramesFn) {
+        let _ = self.state.lock().unwrap().on_dropped.insert(f);
+    }
 }
 
 impl ElementImpl for WebRtcReduxSender {
@@ -71,13 +246,14 @@ impl ElementImpl for WebRtcReduxSender {
     fn pad_templates() -> &'static [gst::PadTemplate] {
         static PAD_TEMPLATES: Lazy<Vec<gst::PadTemplate>> = Lazy::new(|| {
             let caps = gst::Caps::builder_full()
-                .structure(gst::Structure::builder("audio/x-opus").build())
-                .structure(gst::Structure::builder("audio/G722").build())
-                .structure(gst::Structure::builder("audio/x-mulaw").build())
-                .structure(gst::Structure::builder("audio/x-alaw").build())
-                .structure(gst::Structure::builder("video/x-h264").field("stream-format", "byte-stream").field("profile", "baseline").build())
+                .structure(gst::Structure::builder("audio/x-opus").field("rate", gst::List::new(OPUS_SAMPLE_RATES)).build())
+                .structure(gst::Structure::builder("audio/G722").field("rate", 16000i32).build())
+                .structure(gst::Structure::builder("audio/x-mulaw").field("rate", 8000i32).build())
+                .structure(gst::Structure::builder("audio/x-alaw").field("rate", 8000i32).build())
+                .structure(gst::Structure::builder("video/x-h264").field("stream-format", "byte-stream").field("profile", gst::List::new(H264_PROFILES)).build())
                 .structure(gst::Structure::builder("video/x-vp8").build())
                 .structure(gst::Structure::builder("video/x-vp9").build())
+                .structure(gst::Structure::builder("application/x-rtp").field("encoding-name", gst::List::new(RTP_VIDEO_ENCODINGS.into_iter().chain(RTP_AUDIO_ENCODINGS))).build())
                 .build();
             let sink_pad_template = gst::PadTemplate::new(
                 "sink",
@@ -108,41 +284,261 @@ impl ElementImpl for WebRtcReduxSender {
 }
 
 impl BaseSinkImpl for WebRtcReduxSender {
+    fn caps(&self, filter: Option<&gst::Caps>) -> Option<gst::Caps> {
+        let (rtp, auto_mode) = {
+            let state = self.state.lock().unwrap();
+            (state.mode == TrackMode::Rtp, state.auto_mode)
+        };
+        let template_caps = self.instance().static_pad("sink").unwrap().pad_template_caps();
+
+        // Only offer the caps of the pad's mode so upstream can't negotiate the other one
+        let mut caps = gst::Caps::new_empty();
+        {
+            let caps = caps.get_mut().unwrap();
+            for structure in template_caps.iter().filter(|structure| auto_mode || (structure.name() == "application/x-rtp") == rtp) {
+                caps.append_structure(structure.to_owned());
+            }
+        }
+
+        Some(match filter {
+            Some(filter) => filter.intersect_with_mode(&caps, gst::CapsIntersectMode::First),
+            None => caps,
+        })
+    }
+
+    fn prepare(&self, _buffer: &Buffer) -> Result<FlowSuccess, FlowError> {
+        // Runs before prerolling, which would wait for a PLAYING state that is only reached once the track is set up
+        self.check_track()
+    }
+
     fn render(&self, buffer: &Buffer) -> Result<FlowSuccess, FlowError> {
-        let sample_duration = if *self.state.lock().unwrap().media_type.as_ref().unwrap() == MediaType::Video {
-            Duration::from_secs(1)
-        } else {
-            Duration::from_millis(buffer.duration().unwrap().mseconds())
+        self.check_track()?;
+        if self.state.lock().unwrap().muted {
+            trace!(CAT, "Muted, discarding {} bytes", buffer.size());
+            self.count_dropped(buffer);
+            return Ok(gst::FlowSuccess::Ok);
+        }
+
+        if self.state.lock().unwrap().mode == TrackMode::Rtp {
+            return self.render_rtp(buffer);
+        }
+
+        let handle = self.state.lock().unwrap().handle.as_ref().unwrap().clone();
+        let track = match self.state.lock().unwrap().track.as_ref().unwrap() {
+            Track::Sample(track) => track.clone(),
+            Track::Rtp(_) => unreachable!(),
         };
+        let clock_rate = clock_rate(&track.codec().mime_type);
+        let (gap, samples, duration) = self.sample_timing(buffer, clock_rate);
 
-        // If the clock hasn't been set, set it from the buffer timestamp
+        // If the clock hasn't been set, set it from the buffer duration
         if self.state.lock().unwrap().duration.is_none() {
-            let _ = self.state.lock().unwrap().duration.insert(buffer.duration().unwrap());
-            self.set_clock(Some(&format_clock(buffer.duration().unwrap())));
+            let _ = self.state.lock().unwrap().duration.insert(duration);
+            self.set_clock(Some(&format_clock(duration)));
         }
 
         let map = buffer.map_readable().map_err(|_| gst::FlowError::Error)?;
         let media_type_str = if *self.state.lock().unwrap().media_type.as_ref().unwrap() == MediaType::Video { "VIDEO" } else { "AUDIO" };
-        trace!(CAT, "[{}] Rendering {} bytes for duration {} ms", media_type_str, map.size(), sample_duration.as_millis());
+        trace!(CAT, "[{}] Rendering {} bytes for {} RTP ticks after a gap of {}", media_type_str, map.size(), samples, gap);
         let bytes = Bytes::copy_from_slice(map.as_slice());
 
-        let handle = self.state.lock().unwrap().handle.as_ref().unwrap().clone();
-        let track = self.state.lock().unwrap().track.as_ref().unwrap().clone();
+        let mut extensions = self.state.lock().unwrap().extensions.clone().map(|f| f()).unwrap_or_default();
+        extensions.extend(meta_header_extensions(buffer));
+        if let Err(e) = self.pace() {
+            self.count_dropped(buffer);
+            return Err(e);
+        }
         let inner = handle.clone();
         block_on(async move {
             handle.spawn_blocking(move || {
                 inner.block_on(async move {
-                    track.write_sample(&Sample {
+                    // An empty sample only advances the RTP timestamp, so the sample starts at its buffer's time
+                    if gap > 0 {
+                        track.write_sample(&Sample {
+                            duration: ticks_duration(gap, clock_rate),
+                            ..Sample::default()
+                        }).await?;
+                    }
+                    track.write_sample_with_extensions(&Sample {
                         data: bytes,
-                        duration: sample_duration,
+                        duration: ticks_duration(samples, clock_rate),
                         ..Sample::default()
-                    }).await
+                    }, &extensions).await
                 })
             }).await
         }).unwrap().unwrap();
 
+        self.check_first_keyframe(buffer);
+
         Ok(gst::FlowSuccess::Ok)
     }
+
+    fn event(&self, event: gst::Event) -> bool {
+        match event.view() {
+            gst::EventView::FlushStop(_) => {
+                debug!(CAT, "Flushed, resetting pacing and sample timing");
+                let mut state = self.state.lock().unwrap();
+                state.next_write = None;
+                state.timing = None;
+            }
+            gst::EventView::Segment(segment) => {
+                // A new segment starts a new time base, samples are paced relative to its first buffer
+                debug!(CAT, "New segment {:?}", segment.segment());
+                self.state.lock().unwrap().next_write = None;
+            }
+            _ => {}
+        }
+
+        self.parent_event(event)
+    }
+
+    fn unlock(&self) -> Result<(), gst::ErrorMessage> {
+        self.state.lock().unwrap().flushing = true;
+        self.unlock.notify_all();
+        Ok(())
+    }
+
+    fn unlock_stop(&self) -> Result<(), gst::ErrorMessage> {
+        self.state.lock().unwrap().flushing = false;
+        Ok(())
+    }
+}
+
+impl WebRtcReduxSender {
+    /// Returns `FlowError::NotNegotiated` until `add_info` set the track up
+    fn check_track(&self) -> Result<FlowSuccess, FlowError> {
+        let state = self.state.lock().unwrap();
+        if state.media_type.is_none() || state.track.is_none() || state.handle.is_none() {
+            warning!(CAT, "Received a buffer before the track was set up");
+            return Err(gst::FlowError::NotNegotiated);
+        }
+        Ok(gst::FlowSuccess::Ok)
+    }
+
+    fn render_rtp(&self, buffer: &Buffer) -> Result<FlowSuccess, FlowError> {
+        let map = buffer.map_readable().map_err(|_| gst::FlowError::Error)?;
+        let packet = Packet::unmarshal(&mut map.as_slice()).map_err(|e| {
+            error!(CAT, "Failed to parse RTP packet: {:?}", e);
+            gst::FlowError::Error
+        })?;
+        trace!(CAT, "Forwarding RTP packet {} ({} bytes)", packet.header.sequence_number, map.size());
+
+        let handle = self.state.lock().unwrap().handle.as_ref().unwrap().clone();
+        let track = match self.state.lock().unwrap().track.as_ref().unwrap() {
+            Track::Rtp(track) => track.clone(),
+            Track::Sample(_) => unreachable!(),
+        };
+        let mut extensions = self.state.lock().unwrap().extensions.clone().map(|f| f()).unwrap_or_default();
+        extensions.extend(meta_header_extensions(buffer));
+        let inner = handle.clone();
+        block_on(async move {
+            handle.spawn_blocking(move || {
+                inner.block_on(async move {
+                    track.write_rtp_with_extensions(&packet, &extensions).await
+                })
+            }).await
+        }).unwrap().unwrap();
+
+        self.check_first_keyframe(buffer);
+
+        Ok(gst::FlowSuccess::Ok)
+    }
+
+    /// Returns the RTP ticks between the previous sample's end and `buffer`'s start, the ticks of `buffer` itself and
+    /// its duration
+    ///
+    /// Ticks are derived from the running time of the PTS, or the DTS without one, so rounding doesn't accumulate and
+    /// gaps like dropped frames keep their length. Buffers without timestamps follow the previous sample.
+    fn sample_timing(&self, buffer: &BufferRef, clock_rate: u32) -> (u32, u32, ClockTime) {
+        let segment = self.instance().segment();
+        let mut state = self.state.lock().unwrap();
+
+        let default_duration = if state.media_type == Some(MediaType::Video) {
+            ClockTime::from_mseconds(33)
+        } else {
+            ClockTime::from_mseconds(20)
+        };
+        if let Some(duration) = buffer.duration() {
+            state.last_duration = Some(duration);
+        }
+        let duration = state.last_duration.or(state.duration).unwrap_or(default_duration);
+        let ticks = |time: ClockTime| (time.nseconds() as u128 * clock_rate as u128 / 1_000_000_000) as u64;
+
+        let running_time = buffer
+            .pts()
+            .or_else(|| buffer.dts())
+            .and_then(|time| segment.downcast_ref::<ClockTime>()?.to_running_time(time));
+        let running_time = match running_time {
+            Some(running_time) => running_time,
+            None => return (0, ticks(duration) as u32, duration),
+        };
+
+        let (base, written) = *state.timing.get_or_insert((running_time, 0));
+        let start = ticks(running_time.saturating_sub(base));
+        let end = ticks(running_time.saturating_sub(base) + duration);
+        // Samples before the previous one's end, e.g. reordered frames, start right after it
+        let gap = start.saturating_sub(written);
+        let samples = end.saturating_sub(written.max(start));
+        state.timing = Some((base, written.max(end)));
+
+        (gap as u32, samples as u32, duration)
+    }
+
+    /// Blocks until the previous paced sample's duration has passed, bursts of samples are spread out while
+    /// a sender that falls behind writes immediately again. The packets of one sample are written together.
+    ///
+    /// Returns `FlowError::Flushing` if a flush interrupts the wait, the sample is dropped then.
+    fn pace(&self) -> Result<(), FlowError> {
+        let mut state = self.state.lock().unwrap();
+        let interval = match (state.frame_pacing, state.duration) {
+            (true, Some(duration)) => Duration::from_nanos(duration.nseconds()),
+            _ => return Ok(()),
+        };
+
+        let now = Instant::now();
+        let write_at = match state.next_write {
+            Some(next_write) if next_write > now => {
+                trace!(CAT, "Pacing sample by {} us", (next_write - now).as_micros());
+                state = self.unlock.wait_timeout_while(state, next_write - now, |state| !state.flushing).unwrap().0;
+                if state.flushing {
+                    return Err(FlowError::Flushing);
+                }
+                next_write
+            }
+            _ => now,
+        };
+        state.next_write = Some(write_at + interval);
+
+        Ok(())
+    }
+
+    fn count_dropped(&self, buffer: &Buffer) {
+        let (dropped, on_dropped) = {
+            let mut state = self.state.lock().unwrap();
+            if buffer.flags().contains(gst::BufferFlags::DELTA_UNIT) {
+                state.dropped.delta_frames += 1;
+            } else {
+                state.dropped.keyframes += 1;
+            }
+            (state.dropped, state.on_dropped.clone())
+        };
+        trace!(CAT, "Dropped frame, {} keyframes and {} delta frames dropped so far", dropped.keyframes, dropped.delta_frames);
+
+        // Called without the state lock, the callback may read the counters again
+        if let Some(on_dropped) = on_dropped {
+            on_dropped(dropped);
+        }
+    }
+
+    fn check_first_keyframe(&self, buffer: &Buffer) {
+        if *self.state.lock().unwrap().media_type.as_ref().unwrap() == MediaType::Video && !buffer.flags().contains(gst::BufferFlags::DELTA_UNIT) {
+            let on_first_keyframe = self.state.lock().unwrap().on_first_keyframe.take();
+            if let Some(f) = on_first_keyframe {
+                debug!(CAT, "First keyframe sent");
+                f();
+            }
+        }
+    }
 }
 
 #[glib::object_subclass]
@@ -156,6 +552,11 @@ impl ObjectImpl for WebRtcReduxSender {}
 
 impl GstObjectImpl for WebRtcReduxSender {}
 
+/// Duration webrtc-rs converts back to exactly `ticks`, it truncates the ticks it computes
+fn ticks_duration(ticks: u32, clock_rate: u32) -> Duration {
+    Duration::from_secs_f64((ticks as f64 + 0.5) / clock_rate as f64)
+}
+
 fn format_clock(duration: ClockTime) -> gst::Clock {
     let clock = gst::SystemClock::obtain();
     let _ = clock.new_periodic_id(clock.internal_time(), duration);
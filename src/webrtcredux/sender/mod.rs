@@ -1,5 +1,3 @@
-use std::sync::Arc;
-
 use gst::{glib, ClockTime};
 use gst::subclass::prelude::ObjectSubclassExt;
 
@@ -7,16 +5,51 @@ mod imp;
 
 pub use imp::*;
 use tokio::runtime::Handle;
-use webrtc::track::track_local::track_local_static_sample::TrackLocalStaticSample;
 
 glib::wrapper! {
     pub struct WebRtcReduxSender(ObjectSubclass<imp::WebRtcReduxSender>) @extends gst_base::BaseSink, gst::Element, gst::Object;
 }
 
 impl WebRtcReduxSender {
-    pub fn add_info(&self, track: Arc<TrackLocalStaticSample>, handle: Handle, media_type: MediaType, duration: Option<ClockTime>, on_connect: tokio::sync::oneshot::Receiver<()>) {
+    pub fn add_info(&self, track: Track, handle: Handle, media_type: MediaType, duration: Option<ClockTime>, on_connect: tokio::sync::oneshot::Receiver<()>) {
         imp::WebRtcReduxSender::from_instance(self).add_info(track, handle, media_type, duration, on_connect);
     }
+
+    pub fn set_track(&self, track: Track) {
+        imp::WebRtcReduxSender::from_instance(self).set_track(track);
+    }
+
+    pub fn set_track_mode(&self, mode: TrackMode) {
+        imp::WebRtcReduxSender::from_instance(self).set_track_mode(mode);
+    }
+
+    pub fn set_auto_track_mode(&self) {
+        imp::WebRtcReduxSender::from_instance(self).set_auto_track_mode();
+    }
+
+    pub fn set_header_extensions(&self, extensions: HeaderExtensionsFn) {
+        imp::WebRtcReduxSender::from_instance(self).set_header_extensions(extensions);
+    }
+
+    pub fn set_on_first_keyframe(&self, f: Box<dyn FnOnce() + Send>) {
+        imp::WebRtcReduxSender::from_instance(self).set_on_first_keyframe(f);
+    }
+
+    pub fn set_frame_pacing(&self, frame_pacing: bool) {
+        imp::WebRtcReduxSender::from_instance(self).set_frame_pacing(frame_pacing);
+    }
+
+    pub fn set_muted(&self, muted: bool) {
+        imp::WebRtcReduxSender::from_instance(self).set_muted(muted);
+    }
+
+    pub fn dropped_frames(&self) -> DroppedFrames {
+        imp::WebRtcReduxSender::from_instance(self).dropped_frames()
+    }
+
+    pub fn set_on_dropped(&self, f: DroppedFramesFn) {
+        imp::WebRtcReduxSender::from_instance(self).set_on_dropped(f);
+    }
 }
 
 unsafe impl Send for WebRtcReduxSender {}
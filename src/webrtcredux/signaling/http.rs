@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+use gst::debug;
+use reqwest::header::{HeaderMap, CONTENT_TYPE};
+use reqwest::redirect::Policy;
+use reqwest::{Client, Method};
+use url::Url;
+
+use crate::webrtcredux::error::WebRtcReduxError;
+use crate::webrtcredux::CAT;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Redirects followed for a single request
+const MAX_REDIRECTS: usize = 3;
+
+pub(crate) struct Response {
+    pub(crate) status: u16,
+    headers: HeaderMap,
+    pub(crate) body: String,
+    /// URL the response came from after following redirects, relative `Location`s resolve against it
+    pub(crate) url: Url,
+}
+
+impl Response {
+    /// Header names are compared case-insensitively
+    pub(crate) fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|value| value.to_str().ok())
+    }
+
+    pub(crate) fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// Builds a client verifying servers against the platform's CA certificates
+pub(crate) fn client() -> Result<Client, WebRtcReduxError> {
+    Client::builder()
+        .timeout(TIMEOUT)
+        .redirect(Policy::limited(MAX_REDIRECTS))
+        .build()
+        .map_err(|e| WebRtcReduxError::SignalingFailed("create HTTP client".to_string(), e.to_string()))
+}
+
+/// Sends a single HTTP request, `body` is the content type and the content
+pub(crate) async fn request(
+    client: &Client,
+    method: Method,
+    url: &Url,
+    headers: &[(&str, &str)],
+    body: Option<(&str, &str)>,
+) -> Result<Response, WebRtcReduxError> {
+    debug!(CAT, "{} {}", method, url);
+    let failed = |e: reqwest::Error| WebRtcReduxError::SignalingFailed(format!("{} {}", method, url), e.to_string());
+
+    let mut request = client.request(method.clone(), url.clone());
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    request = match body {
+        Some((content_type, body)) => request.header(CONTENT_TYPE, content_type).body(body.to_string()),
+        None => request.body(""),
+    };
+
+    let response = request.send().await.map_err(failed)?;
+    let status = response.status().as_u16();
+    let headers = response.headers().clone();
+    let url = response.url().clone();
+    let body = response.text().await.map_err(failed)?;
+
+    Ok(Response { status, headers, body, url })
+}
@@ -0,0 +1,4 @@
+mod http;
+
+pub(crate) mod whep;
+pub(crate) mod whip;
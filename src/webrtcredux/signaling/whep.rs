@@ -0,0 +1,7 @@
+use webrtc::rtp_transceiver::rtp_codec::RTPCodecType;
+
+/// WHEP sessions are set up and torn down like WHIP ones with `WhipClient`, only the direction differs
+pub(crate) const PROTOCOL: &str = "WHEP";
+
+/// Kinds of the receive-only transceivers a WHEP offer has, endpoints serve at most one track of each
+pub(crate) const RECEIVED_KINDS: [RTPCodecType; 2] = [RTPCodecType::Video, RTPCodecType::Audio];
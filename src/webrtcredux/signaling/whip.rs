@@ -0,0 +1,133 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use gst::{debug, warning};
+use reqwest::{Client, Method};
+use url::Url;
+
+use super::http;
+use crate::webrtcredux::error::WebRtcReduxError;
+use crate::webrtcredux::CAT;
+
+pub(crate) const PROTOCOL: &str = "WHIP";
+
+/// Offer/answer exchange and trickle ICE with a WHIP endpoint (RFC 9725), WHEP endpoints follow the same exchange
+pub(crate) struct WhipClient {
+    /// Protocol name used in errors and logs
+    protocol: &'static str,
+    endpoint: Url,
+    auth_token: Option<String>,
+    client: Client,
+    /// Session URL returned in the `Location` of the answer
+    resource: Mutex<Option<Url>>,
+    etag: Mutex<Option<String>>,
+    /// Cleared once the endpoint answered a PATCH with 405 or 501
+    trickle: AtomicBool,
+}
+
+impl WhipClient {
+    pub(crate) fn new(protocol: &'static str, endpoint: &str, auth_token: Option<String>) -> Result<Self, WebRtcReduxError> {
+        let endpoint = Url::parse(endpoint).map_err(|e| WebRtcReduxError::SignalingFailed(format!("parse endpoint {}", endpoint), e.to_string()))?;
+
+        Ok(WhipClient {
+            protocol,
+            endpoint,
+            auth_token,
+            client: http::client()?,
+            resource: Mutex::new(None),
+            etag: Mutex::new(None),
+            trickle: AtomicBool::new(true),
+        })
+    }
+
+    async fn request(&self, method: Method, url: &Url, mut headers: Vec<(&str, String)>, body: Option<(&str, &str)>) -> Result<http::Response, WebRtcReduxError> {
+        if let Some(token) = &self.auth_token {
+            headers.push(("Authorization", format!("Bearer {}", token)));
+        }
+        let headers = headers.iter().map(|(name, value)| (*name, value.as_str())).collect::<Vec<_>>();
+
+        http::request(&self.client, method, url, &headers, body).await
+    }
+
+    fn failed(&self, operation: &str, response: &http::Response) -> WebRtcReduxError {
+        WebRtcReduxError::SignalingFailed(format!("{} {}", operation, self.protocol), format!("status {}: {}", response.status, response.body.trim()))
+    }
+
+    /// Posts the offer and returns the answer, the session URL is kept for trickling and terminating
+    ///
+    /// Redirects of the endpoint are followed by the HTTP client, a relative session URL is resolved against the
+    /// endpoint that answered.
+    pub(crate) async fn publish(&self, offer: &str) -> Result<String, WebRtcReduxError> {
+        let response = self
+            .request(Method::POST, &self.endpoint, vec![("Accept", "application/sdp".to_string())], Some(("application/sdp", offer)))
+            .await?;
+        if response.status != 201 {
+            return Err(self.failed("post offer to", &response));
+        }
+        if response.url != self.endpoint {
+            debug!(CAT, "{} endpoint redirected to {}", self.protocol, response.url);
+        }
+
+        match response.header("Location").and_then(|location| response.url.join(location).ok()) {
+            Some(location) => {
+                let _ = self.resource.lock().unwrap().insert(location);
+            }
+            None => warning!(CAT, "{} answer has no session URL, trickling and terminating is not possible", self.protocol),
+        }
+        *self.etag.lock().unwrap() = response.header("ETag").map(str::to_string);
+        Ok(response.body)
+    }
+
+    /// Sends an `application/trickle-ice-sdpfrag` to the session, does nothing if the endpoint doesn't support it
+    pub(crate) async fn trickle(&self, fragment: &str) -> Result<(), WebRtcReduxError> {
+        let resource = match self.resource.lock().unwrap().clone() {
+            Some(resource) if self.trickle.load(Ordering::SeqCst) => resource,
+            _ => return Ok(()),
+        };
+
+        let mut headers = vec![];
+        if let Some(etag) = self.etag.lock().unwrap().clone() {
+            headers.push(("If-Match", etag));
+        }
+        let response = self
+            .request(Method::PATCH, &resource, headers, Some(("application/trickle-ice-sdpfrag", fragment)))
+            .await?;
+
+        match response.status {
+            405 | 501 => {
+                debug!(CAT, "{} endpoint doesn't support trickle ICE", self.protocol);
+                self.trickle.store(false, Ordering::SeqCst);
+                Ok(())
+            }
+            _ if response.is_success() => Ok(()),
+            _ => Err(self.failed("trickle candidates to", &response)),
+        }
+    }
+
+    /// Deletes the session, failures are only logged since the connection is going away anyway
+    pub(crate) async fn terminate(&self) {
+        let resource = match self.resource.lock().unwrap().take() {
+            Some(resource) => resource,
+            None => return,
+        };
+
+        match self.request(Method::DELETE, &resource, vec![], None).await {
+            Ok(response) if !response.is_success() => warning!(CAT, "{}", self.failed("terminate session with", &response)),
+            Err(e) => warning!(CAT, "Failed to terminate {} session: {}", self.protocol, e),
+            _ => (),
+        }
+    }
+}
+
+/// Builds the SDP fragment of RFC 8840 for `candidates` of the bundled section `mid`, without `a=` prefixes in
+/// `candidates`
+pub(crate) fn sdp_fragment(ufrag: &str, pwd: &str, mid: &str, candidates: &[String], end_of_candidates: bool) -> String {
+    let mut fragment = format!("a=ice-ufrag:{}\r\na=ice-pwd:{}\r\nm=audio 9 UDP/TLS/RTP/SAVPF 0\r\na=mid:{}\r\n", ufrag, pwd, mid);
+    for candidate in candidates {
+        fragment += &format!("a={}\r\n", candidate);
+    }
+    if end_of_candidates {
+        fragment += "a=end-of-candidates\r\n";
+    }
+    fragment
+}